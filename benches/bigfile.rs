@@ -1,5 +1,8 @@
-use criterion::{Criterion, criterion_group, criterion_main};
+use criterion::{
+    BenchmarkGroup, Criterion, criterion_group, criterion_main, measurement::WallTime,
+};
 use mt_rs::{
+    flat::FlatMerkleTree,
     hasher::{Blake3Hasher, Hasher, Keccak256Hasher, SHA256Hasher},
     merkletree::MerkleTree,
     proof::{DefaultProofer, Proofer},
@@ -10,7 +13,7 @@ use std::io::{BufWriter, Write};
 use std::path::Path;
 
 // Create files `filenames` with random data with a size of `size` MB.
-fn setup_files(filenames: &Vec<String>, size: usize) -> std::io::Result<Vec<Vec<u8>>> {
+fn setup_files(filenames: &[String], size: usize) -> std::io::Result<Vec<Vec<u8>>> {
     for filename in filenames.iter() {
         if !Path::new(filename).exists() {
             let file = File::create(filename)?;
@@ -37,7 +40,7 @@ fn setup_files(filenames: &Vec<String>, size: usize) -> std::io::Result<Vec<Vec<
     Ok(files)
 }
 
-fn cleanup_files(filenames: &Vec<String>) -> std::io::Result<()> {
+fn cleanup_files(filenames: &[String]) -> std::io::Result<()> {
     for filename in filenames.iter() {
         if Path::new(filename).exists() {
             fs::remove_file(filename)?;
@@ -55,9 +58,9 @@ fn test_merkle_tree<H: Hasher + Clone + 'static + std::marker::Sync>(
     let root = tree.root();
     let root_hash = root.hash();
 
-    for i in 0..files.len() {
+    for (i, file) in files.iter().enumerate() {
         let proof = proofer.generate(i).expect("proof generation failed");
-        assert!(proofer.verify(&proof, &files[i], root_hash));
+        assert!(proofer.verify(&proof, file, root_hash));
     }
 }
 
@@ -136,10 +139,105 @@ fn bench_large_merkle_tree_blake3(c: &mut Criterion) {
     group.finish();
 }
 
+/// Number of leaves of the trees used by the proof benchmarks.
+const PROOF_BENCH_LEAVES: usize = 1024;
+
+// Create `count` leaves of 1 KB of random data each.
+fn setup_leaves(count: usize) -> Vec<Vec<u8>> {
+    (0..count)
+        .map(|_| {
+            let mut buffer = vec![0u8; 1024];
+            let _ = OsRng.try_fill_bytes(&mut buffer);
+            buffer
+        })
+        .collect()
+}
+
+fn bench_generation_with<H: Hasher + Clone + 'static + std::marker::Sync>(
+    group: &mut BenchmarkGroup<WallTime>,
+    name: &str,
+    hasher: H,
+) {
+    let leaves = setup_leaves(PROOF_BENCH_LEAVES);
+    let tree = MerkleTree::new(hasher.clone(), &leaves);
+    let flat = FlatMerkleTree::new(hasher.clone(), &leaves);
+    let proofer = DefaultProofer::new(hasher, tree.leaves());
+
+    group.bench_function(
+        format!("Proof generation for one of {PROOF_BENCH_LEAVES} leaves with {name} algorithm."),
+        |b| {
+            let mut index = 0;
+            b.iter(|| {
+                index = (index + 1) % PROOF_BENCH_LEAVES;
+                proofer.generate(index).expect("proof generation failed")
+            });
+        },
+    );
+
+    // The flat tree needs no proofer: siblings are read by index arithmetic.
+    group.bench_function(
+        format!(
+            "Flat tree proof generation for one of {PROOF_BENCH_LEAVES} leaves with {name} algorithm."
+        ),
+        |b| {
+            let mut index = 0;
+            b.iter(|| {
+                index = (index + 1) % PROOF_BENCH_LEAVES;
+                flat.proof(index).expect("proof generation failed")
+            });
+        },
+    );
+}
+
+fn bench_verification_with<H: Hasher + Clone + 'static + std::marker::Sync>(
+    group: &mut BenchmarkGroup<WallTime>,
+    name: &str,
+    hasher: H,
+) {
+    let leaves = setup_leaves(PROOF_BENCH_LEAVES);
+    let tree = MerkleTree::new(hasher.clone(), &leaves);
+    let proofer = DefaultProofer::new(hasher, tree.leaves());
+    let root = tree.root();
+    let proofs: Vec<_> = (0..PROOF_BENCH_LEAVES)
+        .map(|i| proofer.generate(i).expect("proof generation failed"))
+        .collect();
+
+    group.bench_function(
+        format!("Proof verification for one of {PROOF_BENCH_LEAVES} leaves with {name} algorithm."),
+        |b| {
+            let mut index = 0;
+            b.iter(|| {
+                index = (index + 1) % PROOF_BENCH_LEAVES;
+                assert!(proofer.verify(&proofs[index], &leaves[index], root.hash()));
+            });
+        },
+    );
+}
+
+/// Measures only the generation of a single proof on a prebuilt tree, for each hasher.
+fn bench_proof_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ProofGeneration");
+    bench_generation_with(&mut group, "SHA256", SHA256Hasher::new());
+    bench_generation_with(&mut group, "Keccak256", Keccak256Hasher::new());
+    bench_generation_with(&mut group, "Blake3", Blake3Hasher::new());
+    group.finish();
+}
+
+/// Measures only the verification of a single proof on a prebuilt tree, for each hasher.
+fn bench_proof_verification(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ProofVerification");
+    bench_verification_with(&mut group, "SHA256", SHA256Hasher::new());
+    bench_verification_with(&mut group, "Keccak256", Keccak256Hasher::new());
+    bench_verification_with(&mut group, "Blake3", Blake3Hasher::new());
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_large_merkle_tree_sha256,
     bench_large_merkle_tree_keccak256,
-    bench_large_merkle_tree_blake3
+    bench_large_merkle_tree_blake3,
+    bench_proof_generation,
+    bench_proof_verification
 );
 criterion_main!(benches);
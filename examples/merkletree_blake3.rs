@@ -14,5 +14,5 @@ fn main() {
 
     let tree = MerkleTree::from_paths(hasher, filenames);
 
-    println!("{}", tree.root().hash());
+    println!("{}", tree.root_hex());
 }
@@ -0,0 +1,216 @@
+//! Encodes digests, such as root hashes, in the text formats used by different ecosystems.
+//!
+//! Hashes are handled as hex strings throughout the library; `Digest` holds their raw bytes
+//! to convert them from and to the other encodings. Every encoding besides hex is behind its
+//! own Cargo feature (`base64`, `base58` and `multibase`).
+
+use crate::error::ParseError;
+#[cfg(feature = "generic-array")]
+use digest::generic_array::{ArrayLength, GenericArray};
+
+/// A text encoding of digest bytes.
+///
+/// Variants depend on the enabled features, so matches need a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// Lowercase hex, as returned by the hashers. Any case is accepted when decoding.
+    Hex,
+    /// Standard base64 (RFC 4648), with padding.
+    #[cfg(feature = "base64")]
+    Base64,
+    /// Base58 with the Bitcoin alphabet.
+    #[cfg(feature = "base58")]
+    Base58,
+    /// Self-describing multibase string. Encoding uses base58btc (prefix `z`); decoding
+    /// accepts any multibase prefix.
+    #[cfg(feature = "multibase")]
+    Multibase,
+}
+
+/// The raw bytes of a hash.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Digest {
+    bytes: Vec<u8>,
+}
+
+impl Digest {
+    /// Creates a digest from its raw bytes.
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self { bytes }
+    }
+
+    /// Creates a digest from a hex hash, as returned by the hashers and `MerkleTree::root_hex`.
+    pub fn from_hex(hash: &str) -> Result<Self, ParseError> {
+        Self::decode(hash, Encoding::Hex)
+    }
+
+    /// Returns the raw bytes of the digest.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the digest as lowercase hex.
+    pub fn to_hex(&self) -> String {
+        self.encode(Encoding::Hex)
+    }
+
+    /// Encodes the digest with `encoding`.
+    pub fn encode(&self, encoding: Encoding) -> String {
+        match encoding {
+            Encoding::Hex => hex::encode(&self.bytes),
+            #[cfg(feature = "base64")]
+            Encoding::Base64 => {
+                use base64::Engine;
+                base64::engine::general_purpose::STANDARD.encode(&self.bytes)
+            }
+            #[cfg(feature = "base58")]
+            Encoding::Base58 => bs58::encode(&self.bytes).into_string(),
+            #[cfg(feature = "multibase")]
+            Encoding::Multibase => multibase::encode(multibase::Base::Base58Btc, &self.bytes),
+        }
+    }
+
+    /// Decodes a digest encoded with `encoding`, the inverse of `encode`.
+    ///
+    /// # Returns
+    ///
+    /// The digest, `ParseError::InvalidHex` if a hex input is invalid, or
+    /// `ParseError::InvalidEncoding` for any other invalid input.
+    pub fn decode(input: &str, encoding: Encoding) -> Result<Self, ParseError> {
+        let bytes = match encoding {
+            Encoding::Hex => hex::decode(input).map_err(|_| ParseError::InvalidHex)?,
+            #[cfg(feature = "base64")]
+            Encoding::Base64 => {
+                use base64::Engine;
+                base64::engine::general_purpose::STANDARD
+                    .decode(input)
+                    .map_err(|_| ParseError::InvalidEncoding)?
+            }
+            #[cfg(feature = "base58")]
+            Encoding::Base58 => bs58::decode(input)
+                .into_vec()
+                .map_err(|_| ParseError::InvalidEncoding)?,
+            #[cfg(feature = "multibase")]
+            Encoding::Multibase => {
+                multibase::decode(input)
+                    .map_err(|_| ParseError::InvalidEncoding)?
+                    .1
+            }
+        };
+
+        Ok(Self::new(bytes))
+    }
+
+    /// Returns the digest as a `GenericArray` of the RustCrypto crates, e.g.
+    /// `GenericArray<u8, U32>`.
+    ///
+    /// # Returns
+    ///
+    /// The array, or `None` if the digest is not `N` bytes long.
+    #[cfg(feature = "generic-array")]
+    pub fn to_generic_array<N>(&self) -> Option<GenericArray<u8, N>>
+    where
+        N: ArrayLength<u8>,
+    {
+        GenericArray::from_exact_iter(self.bytes.iter().copied())
+    }
+}
+
+#[cfg(feature = "generic-array")]
+impl<N> From<GenericArray<u8, N>> for Digest
+where
+    N: ArrayLength<u8>,
+{
+    /// Wraps a digest returned by a RustCrypto hasher, e.g. `Sha256::digest`, so it can be
+    /// turned into the hex hashes taken by the proofers with `to_hex`.
+    fn from(array: GenericArray<u8, N>) -> Self {
+        Self::new(array.to_vec())
+    }
+}
+
+impl From<[u8; 32]> for Digest {
+    fn from(array: [u8; 32]) -> Self {
+        Self::new(array.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SHA256 of "abc".
+    const ABC: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    #[test]
+    fn test_hex_round_trip() {
+        let digest = Digest::from_hex(&ABC.to_uppercase()).unwrap();
+        assert_eq!(digest.as_bytes().len(), 32);
+        assert_eq!(digest.to_hex(), ABC);
+        assert_eq!(Digest::from_hex("xyz"), Err(ParseError::InvalidHex));
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_base64_round_trip() {
+        let digest = Digest::from_hex(ABC).unwrap();
+        let encoded = digest.encode(Encoding::Base64);
+        assert_eq!(encoded, "ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=");
+        assert_eq!(Digest::decode(&encoded, Encoding::Base64), Ok(digest));
+        assert_eq!(
+            Digest::decode(
+                "ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0",
+                Encoding::Base64
+            ),
+            Err(ParseError::InvalidEncoding)
+        );
+    }
+
+    #[cfg(feature = "base58")]
+    #[test]
+    fn test_base58_round_trip() {
+        let digest = Digest::new(vec![0, 0, 1, 2, 3]);
+        let encoded = digest.encode(Encoding::Base58);
+        assert_eq!(encoded, "11Ldp");
+        assert_eq!(Digest::decode(&encoded, Encoding::Base58), Ok(digest));
+        assert_eq!(
+            Digest::decode("0OIl", Encoding::Base58),
+            Err(ParseError::InvalidEncoding)
+        );
+    }
+
+    #[cfg(feature = "multibase")]
+    #[test]
+    fn test_multibase_round_trip() {
+        let digest = Digest::from_hex(ABC).unwrap();
+        let encoded = digest.encode(Encoding::Multibase);
+        assert!(encoded.starts_with('z'));
+        assert_eq!(
+            Digest::decode(&encoded, Encoding::Multibase),
+            Ok(digest.clone())
+        );
+        assert_eq!(
+            Digest::decode(&format!("f{ABC}"), Encoding::Multibase),
+            Ok(digest)
+        );
+        assert_eq!(
+            Digest::decode(ABC, Encoding::Multibase),
+            Err(ParseError::InvalidEncoding)
+        );
+    }
+
+    #[cfg(all(feature = "generic-array", feature = "sha256"))]
+    #[test]
+    fn test_generic_array_interop() {
+        use digest::{Digest as _, consts::U20, consts::U32};
+
+        let array = sha2::Sha256::digest(b"abc");
+        let digest = Digest::from(array);
+        assert_eq!(digest.to_hex(), ABC);
+        assert_eq!(digest.to_generic_array::<U32>(), Some(array));
+        assert!(digest.to_generic_array::<U20>().is_none());
+
+        let bytes: [u8; 32] = array.into();
+        assert_eq!(Digest::from(bytes), digest);
+    }
+}
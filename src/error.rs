@@ -0,0 +1,200 @@
+//! Defines the error type returned by the fallible operations of this library.
+
+use std::{fmt, io, path::PathBuf};
+
+/// Errors that can occur while building or querying Merkle trees.
+#[derive(Debug)]
+pub enum MerkleError {
+    /// A file or directory could not be read.
+    Io {
+        /// The path that failed.
+        path: PathBuf,
+        /// The underlying I/O error.
+        source: io::Error,
+    },
+    /// The operation was cancelled through its cancellation flag.
+    Cancelled,
+    /// Some of the paths to hash are missing or cannot be read. Every failing path is listed,
+    /// as found by the upfront check done before hashing anything.
+    UnreadablePaths {
+        /// Each failing path with the error it caused.
+        failures: Vec<(PathBuf, io::Error)>,
+    },
+    /// A sidecar file does not start with a hex hash.
+    InvalidSidecar {
+        /// The path of the sidecar.
+        path: PathBuf,
+    },
+    /// A root file holds neither a hex digest nor a raw one of the expected length.
+    InvalidRootFile {
+        /// The path of the root file.
+        path: PathBuf,
+    },
+    /// A file is larger than the configured maximum size.
+    FileTooLarge {
+        /// The path of the file.
+        path: PathBuf,
+        /// The size of the file, in bytes.
+        size: u64,
+    },
+    /// A leaf index past the end of the tree was given.
+    IndexOutOfBounds {
+        /// The requested index.
+        index: usize,
+        /// The number of leaves in the tree.
+        len: usize,
+    },
+    /// The tree has no room left for another leaf.
+    CapacityExceeded {
+        /// The maximum number of leaves of the tree.
+        capacity: usize,
+    },
+    /// The value is already in the set.
+    DuplicateValue {
+        /// The duplicated value.
+        value: u64,
+    },
+    /// There is nothing to build a tree from, e.g. an empty directory or no paths at all.
+    EmptyInput,
+    /// A leaf was deleted from a tree not built with `TreeOptions::revocable`.
+    NotRevocable,
+    /// A buffer cannot be split into whole records of the given size.
+    InvalidRecordSize {
+        /// The length of the buffer, in bytes.
+        len: usize,
+        /// The size of each record, in bytes.
+        record_size: usize,
+    },
+}
+
+impl fmt::Display for MerkleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MerkleError::Io { path, source } => {
+                write!(f, "Failed to read '{}': {}", path.display(), source)
+            }
+            MerkleError::Cancelled => write!(f, "Operation cancelled"),
+            MerkleError::UnreadablePaths { failures } => {
+                write!(f, "{} paths cannot be read", failures.len())?;
+                for (i, (path, source)) in failures.iter().enumerate() {
+                    let separator = if i == 0 { ": " } else { ", " };
+                    write!(f, "{separator}'{}' ({source})", path.display())?;
+                }
+                Ok(())
+            }
+            MerkleError::InvalidSidecar { path } => {
+                write!(f, "Sidecar '{}' does not hold a hex hash", path.display())
+            }
+            MerkleError::InvalidRootFile { path } => {
+                write!(f, "Root file '{}' does not hold a digest", path.display())
+            }
+            MerkleError::FileTooLarge { path, size } => {
+                write!(f, "File '{}' is too large ({size} bytes)", path.display())
+            }
+            MerkleError::IndexOutOfBounds { index, len } => {
+                write!(f, "Leaf index {index} out of bounds for {len} leaves")
+            }
+            MerkleError::CapacityExceeded { capacity } => {
+                write!(f, "The tree is full ({capacity} leaves)")
+            }
+            MerkleError::DuplicateValue { value } => {
+                write!(f, "Value {value} is already in the set")
+            }
+            MerkleError::EmptyInput => write!(f, "A Merkle tree needs at least one leaf"),
+            MerkleError::NotRevocable => write!(f, "The tree was not built with revocable leaves"),
+            MerkleError::InvalidRecordSize { len, record_size } => write!(
+                f,
+                "A buffer of {len} bytes cannot be split into records of {record_size} bytes"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MerkleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MerkleError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Reasons why the levels of a loaded proofer are rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// There are no levels, or the first level has no leaves.
+    Empty,
+    /// A level does not have half the nodes of the level below, rounded up.
+    LevelSize {
+        /// The level with the wrong size, 0 being the leaves.
+        level: usize,
+        /// The expected number of nodes.
+        expected: usize,
+        /// The actual number of nodes.
+        actual: usize,
+    },
+    /// The top level is not a single root.
+    MissingRoot,
+    /// A node's hash is not the combination of its children's hashes.
+    HashMismatch {
+        /// The level of the node, 0 being the leaves.
+        level: usize,
+        /// The position of the node within its level.
+        index: usize,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::Empty => write!(f, "The tree has no leaves"),
+            ValidationError::LevelSize {
+                level,
+                expected,
+                actual,
+            } => write!(f, "Level {level} has {actual} nodes, expected {expected}"),
+            ValidationError::MissingRoot => write!(f, "The top level is not a single root"),
+            ValidationError::HashMismatch { level, index } => write!(
+                f,
+                "Node {index} of level {level} does not match its children"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Reasons why an encoded proof or hash cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input is not valid hex.
+    InvalidHex,
+    /// The input ends before the encoded proof does.
+    Truncated,
+    /// A path step has a direction byte other than `0` (left) or `1` (right).
+    InvalidDirection(u8),
+    /// Bytes are left after the encoded proof.
+    TrailingBytes,
+    /// The input is not valid in the requested encoding, e.g. base64 with a bad alphabet or
+    /// padding.
+    InvalidEncoding,
+    /// The encoded leaf index does not fit in a `usize` on this platform.
+    IndexOverflow(u64),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidHex => write!(f, "Invalid hex string"),
+            ParseError::Truncated => write!(f, "Encoded proof is truncated"),
+            ParseError::InvalidDirection(byte) => write!(f, "Invalid path direction {byte}"),
+            ParseError::TrailingBytes => write!(f, "Unexpected bytes after the encoded proof"),
+            ParseError::InvalidEncoding => write!(f, "Invalid encoded string"),
+            ParseError::IndexOverflow(index) => {
+                write!(f, "Leaf index {index} does not fit this platform")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
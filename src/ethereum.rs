@@ -0,0 +1,102 @@
+//! Verifies proofs of Merkle trees built the Ethereum way, as checked on-chain by
+//! OpenZeppelin's `MerkleProof.verify`.
+//!
+//! These trees hash leaves and pairs with Keccak256 over raw bytes, with no domain tag, and
+//! sort each pair before hashing it, so proof directions are irrelevant.
+
+use crate::proof::MerkleProof;
+use digest::Digest;
+
+/// Hashes a pair of nodes as OpenZeppelin's `_hashPair`: `keccak256(min(a, b) || max(a, b))`.
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = sha3::Keccak256::new();
+    hasher.update(first);
+    hasher.update(second);
+    hasher.finalize().into()
+}
+
+/// Verifies a proof for ABI-encoded leaf data against a root read from a contract, e.g. the
+/// `bytes32` of an event log.
+///
+/// The leaf is `keccak256(leaf_encoded)`. The sibling directions of `proof` are ignored, since
+/// pairs are sorted before hashing.
+///
+/// # Arguments
+///
+/// * `leaf_encoded` - The ABI-encoded leaf data.
+/// * `proof` - The proof, whose sibling hashes are hex-encoded 32-byte values.
+/// * `root` - The expected root, in its big-endian `bytes32` form.
+///
+/// # Returns
+///
+/// `true` if the proof leads to `root`, `false` if it does not or if a sibling is not a
+/// hex-encoded 32-byte value.
+pub fn verify_abi(leaf_encoded: &[u8], proof: &MerkleProof, root: &[u8; 32]) -> bool {
+    let mut computed: [u8; 32] = sha3::Keccak256::digest(leaf_encoded).into();
+
+    for node in &proof.path {
+        let sibling: [u8; 32] = match hex::decode(&node.hash).map(<[u8; 32]>::try_from) {
+            Ok(Ok(sibling)) => sibling,
+            _ => return false,
+        };
+        computed = hash_pair(&computed, &sibling);
+    }
+
+    &computed == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{node::NodeChildType, proof::ProofNode};
+
+    fn keccak(data: &[u8]) -> [u8; 32] {
+        sha3::Keccak256::digest(data).into()
+    }
+
+    fn proof_of(siblings: &[[u8; 32]]) -> MerkleProof {
+        MerkleProof {
+            path: siblings
+                .iter()
+                .map(|sibling| ProofNode {
+                    hash: hex::encode(sibling),
+                    child_type: NodeChildType::Right,
+                })
+                .collect(),
+            leaf_index: 0,
+            leaf_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_single_leaf_root_is_its_keccak() {
+        let root = hex::decode("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert!(verify_abi(b"", &proof_of(&[]), &root));
+        assert!(!verify_abi(b"\0", &proof_of(&[]), &root));
+    }
+
+    #[test]
+    fn test_verify_sorted_pairs() {
+        let leaves: Vec<[u8; 32]> = (0u8..4).map(|i| keccak(&[i; 32])).collect();
+        let left = hash_pair(&leaves[0], &leaves[1]);
+        let right = hash_pair(&leaves[2], &leaves[3]);
+        let root = hash_pair(&left, &right);
+        assert_eq!(hash_pair(&right, &left), root);
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let sibling = leaves[index ^ 1];
+            let uncle = if index < 2 { right } else { left };
+            let proof = proof_of(&[sibling, uncle]);
+            assert!(verify_abi(&[index as u8; 32], &proof, &root));
+            assert!(!verify_abi(leaf, &proof, &root));
+        }
+
+        let mut proof = proof_of(&[leaves[1], right]);
+        proof.path[1].hash.truncate(62);
+        assert!(!verify_abi(&[0; 32], &proof, &root));
+    }
+}
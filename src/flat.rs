@@ -0,0 +1,218 @@
+//! Provides a Merkle tree stored as a single contiguous array of raw digests.
+
+use crate::{
+    hasher::Hasher,
+    node::NodeChildType,
+    proof::{self, MerkleProof, ProofNode},
+};
+use rayon::prelude::*;
+
+/// A binary Merkle tree holding every hash, level by level, in one contiguous buffer.
+///
+/// Unlike `MerkleTree`, there is no `Node` and no boxed child: the digests of each level
+/// follow those of the level below, without padding, and nodes are found by index
+/// arithmetic. Trees and proofs are identical to the `MerkleTree` and `DefaultProofer` ones.
+///
+/// The hasher must produce hex-encoded digests of a fixed length, as all the provided
+/// algorithms do.
+pub struct FlatMerkleTree<H: Hasher> {
+    hasher: H,
+    /// Raw digests of every level, from the leaves up to the root.
+    hashes: Vec<u8>,
+    /// Length in bytes of each digest.
+    hash_len: usize,
+    /// Position of the first digest of each level, followed by the total number of digests.
+    level_starts: Vec<usize>,
+}
+
+impl<H> FlatMerkleTree<H>
+where
+    H: Hasher,
+{
+    /// Creates a new `FlatMerkleTree` from a collection of data items.
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - A reference to an implementation of the `Hasher` trait.
+    /// * `data` - An iterator of values to be converted into leaves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty, or if the hasher's digests are not hex or vary in length.
+    pub fn new<I, T>(hasher: H, data: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        let mut hashes = Vec::new();
+        let mut hash_len = 0;
+        let mut count = 0;
+        for item in data {
+            let digest = decode(&hasher.hash(item.as_ref()));
+            if count == 0 {
+                hash_len = digest.len();
+            }
+            assert_eq!(digest.len(), hash_len, "digests must have a fixed length");
+            hashes.extend(digest);
+            count += 1;
+        }
+
+        assert!(count > 0, "Merkle Tree requires at least one element");
+
+        let mut level_starts = vec![0, count];
+        while count > 1 {
+            let start = level_starts[level_starts.len() - 2];
+            let level = &hashes[start * hash_len..];
+            let next: Vec<Vec<u8>> = level
+                .par_chunks(2 * hash_len)
+                .map(|pair| {
+                    // The last node of an odd level is paired with itself.
+                    let (left, right) = if pair.len() == 2 * hash_len {
+                        pair.split_at(hash_len)
+                    } else {
+                        (pair, pair)
+                    };
+                    decode(&hasher.combine(&hex::encode(left), &hex::encode(right)))
+                })
+                .collect();
+
+            count = next.len();
+            hashes.extend(next.into_iter().flatten());
+            level_starts.push(hashes.len() / hash_len);
+        }
+
+        Self {
+            hasher,
+            hashes,
+            hash_len,
+            level_starts,
+        }
+    }
+
+    /// Returns the number of leaves.
+    pub fn len(&self) -> usize {
+        self.level_starts[1]
+    }
+
+    /// Returns true if the tree has no leaves (should never happen if `new()` was used).
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the height of the tree, counted in levels as for `MerkleTree::height`.
+    pub fn height(&self) -> usize {
+        self.level_starts.len() - 1
+    }
+
+    /// Returns the raw digest of the node at `position` within `level`, `0` being the leaves.
+    fn hash_at(&self, level: usize, position: usize) -> &[u8] {
+        let start = (self.level_starts[level] + position) * self.hash_len;
+        &self.hashes[start..start + self.hash_len]
+    }
+
+    /// Returns the number of nodes of `level`, without padding.
+    fn level_len(&self, level: usize) -> usize {
+        self.level_starts[level + 1] - self.level_starts[level]
+    }
+
+    /// Returns the raw digest of the root.
+    pub fn root_bytes(&self) -> &[u8] {
+        self.hash_at(self.height() - 1, 0)
+    }
+
+    /// Returns the root hash as a lowercase hex string.
+    pub fn root(&self) -> String {
+        hex::encode(self.root_bytes())
+    }
+
+    /// Generates a Merkle proof for the leaf at `index`, reading each sibling at its computed
+    /// position in the buffer.
+    ///
+    /// # Returns
+    ///
+    /// The proof, identical to the one of `DefaultProofer`, or `None` if `index` is out of
+    /// bounds.
+    pub fn proof(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.len() {
+            return None;
+        }
+
+        let path = (0..self.height() - 1)
+            .map(|level| {
+                let position = index >> level;
+                // The last node of an odd level has no sibling and is paired with itself.
+                let sibling = match position ^ 1 {
+                    sibling if sibling < self.level_len(level) => sibling,
+                    _ => position,
+                };
+                ProofNode {
+                    hash: hex::encode(self.hash_at(level, sibling)),
+                    child_type: if position % 2 == 1 {
+                        NodeChildType::Left
+                    } else {
+                        NodeChildType::Right
+                    },
+                }
+            })
+            .collect();
+
+        Some(MerkleProof {
+            path,
+            leaf_index: index,
+            leaf_hash: Some(hex::encode(self.hash_at(0, index))),
+        })
+    }
+
+    /// Verifies that `data` is the leaf proven by `proof` in the tree with root `root_hash`.
+    pub fn verify<T>(&self, proof: &MerkleProof, data: T, root_hash: &str) -> bool
+    where
+        T: AsRef<[u8]>,
+    {
+        proof::fold_path(&self.hasher, proof, self.hasher.hash(data.as_ref())) == root_hash
+    }
+}
+
+/// Decodes a digest returned by the hasher.
+fn decode(hash: &str) -> Vec<u8> {
+    hex::decode(hash).expect("digests must be hex-encoded")
+}
+
+#[cfg(all(test, feature = "sha256"))]
+mod tests {
+    use super::*;
+    use crate::{
+        hasher::SHA256Hasher,
+        merkletree::MerkleTree,
+        proof::{DefaultProofer, Proofer},
+    };
+
+    #[test]
+    fn test_matches_merkle_tree() {
+        let hasher = SHA256Hasher::new();
+        for count in 1..=17 {
+            let data: Vec<[u8; 1]> = (0..count).map(|i| [i as u8]).collect();
+            let tree = MerkleTree::new(hasher.clone(), &data);
+            let flat = FlatMerkleTree::new(hasher.clone(), &data);
+            let proofer = DefaultProofer::new(hasher.clone(), tree.leaves());
+
+            assert_eq!(flat.root(), tree.root_hex());
+            assert_eq!(flat.height(), tree.height());
+            assert_eq!(flat.len(), count);
+            for (index, item) in data.iter().enumerate() {
+                let proof = flat.proof(index).unwrap();
+                assert_eq!(proof, proofer.generate(index).unwrap());
+                assert!(flat.verify(&proof, item, &flat.root()));
+            }
+            assert!(flat.proof(count).is_none());
+        }
+    }
+
+    #[test]
+    fn test_verify_rejects_wrong_data() {
+        let flat = FlatMerkleTree::new(SHA256Hasher::new(), ["a", "b", "c"]);
+        let proof = flat.proof(0).unwrap();
+
+        assert!(!flat.verify(&proof, "b", &flat.root()));
+        assert_eq!(flat.root_bytes().len(), 32);
+    }
+}
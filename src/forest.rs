@@ -0,0 +1,209 @@
+//! Provides a forest of named Merkle trees sharing the same hasher and options, committed to
+//! by a single forest root.
+//!
+//! The forest root is the root of a tree whose leaves are the roots of the individual trees,
+//! taken in name order. A forest proof therefore locates both the tree, by its position among
+//! the roots, and the leaf within the tree.
+
+use crate::{
+    hasher::Hasher,
+    merkletree::{MerkleTree, MerkleTreeBuilder, TreeOptions},
+    proof::{MerkleProof, fold_path},
+};
+use std::collections::BTreeMap;
+
+/// A proof that a piece of data is a leaf of a named tree of a forest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForestProof {
+    /// The name of the tree holding the leaf.
+    pub name: String,
+    /// The number of leaves of that tree, needed when the tree root commits to it.
+    pub leaf_count: usize,
+    /// The proof of the leaf within its tree.
+    pub leaf_proof: MerkleProof,
+    /// The proof of the tree root among the roots of the forest.
+    pub tree_proof: MerkleProof,
+}
+
+/// A collection of named Merkle trees built with the same hasher and options.
+pub struct MerkleForest<H: Hasher> {
+    hasher: H,
+    options: TreeOptions,
+    trees: BTreeMap<String, MerkleTree>,
+}
+
+impl<H> MerkleForest<H>
+where
+    H: Hasher + 'static + std::marker::Sync + Clone,
+{
+    /// Creates an empty forest whose trees use `hasher` and the default options.
+    pub fn new(hasher: H) -> Self {
+        Self::with_options(hasher, TreeOptions::default())
+    }
+
+    /// Creates an empty forest whose trees use `hasher` and `options`.
+    pub fn with_options(hasher: H, options: TreeOptions) -> Self {
+        Self {
+            hasher,
+            options,
+            trees: BTreeMap::new(),
+        }
+    }
+
+    /// Builds a tree over `data` and stores it under `name`.
+    ///
+    /// # Returns
+    ///
+    /// The tree previously stored under `name`, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    pub fn insert<I, T>(&mut self, name: impl Into<String>, data: I) -> Option<MerkleTree>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        let tree = MerkleTreeBuilder::with_options(self.hasher.clone(), self.options).build(data);
+        self.trees.insert(name.into(), tree)
+    }
+
+    /// Returns the tree stored under `name`.
+    pub fn get(&self, name: &str) -> Option<&MerkleTree> {
+        self.trees.get(name)
+    }
+
+    /// Returns the root hash of the tree stored under `name`.
+    pub fn root_of(&self, name: &str) -> Option<String> {
+        self.trees.get(name).map(MerkleTree::root_hex)
+    }
+
+    /// Returns the number of trees in the forest.
+    pub fn len(&self) -> usize {
+        self.trees.len()
+    }
+
+    /// Returns true if the forest has no trees.
+    pub fn is_empty(&self) -> bool {
+        self.trees.is_empty()
+    }
+
+    /// Returns the forest root: the root of a tree over the roots of every tree, in name order.
+    /// The roots are rehashed on every call, which costs O(number of trees).
+    ///
+    /// # Returns
+    ///
+    /// The forest root, or `None` if the forest is empty.
+    pub fn forest_root(&self) -> Option<String> {
+        self.roots_tree().map(|tree| tree.root_hex())
+    }
+
+    /// Generates the proof of the leaf at `index` of the tree stored under `name`.
+    ///
+    /// # Returns
+    ///
+    /// The proof, or `None` if there is no such tree or leaf.
+    pub fn proof(&self, name: &str, index: usize) -> Option<ForestProof> {
+        let tree = self.trees.get(name)?;
+        let leaf_proof = tree.generate_proof(index)?;
+        let position = self.trees.keys().position(|key| key == name)?;
+        let tree_proof = self.roots_tree()?.generate_proof(position)?;
+
+        Some(ForestProof {
+            name: name.to_string(),
+            leaf_count: tree.len(),
+            leaf_proof,
+            tree_proof,
+        })
+    }
+
+    /// Verifies that `data` is the leaf proven by `proof`, under `forest_root`.
+    ///
+    /// Only the forest's hasher and options are used, so a forest without any tree verifies
+    /// proofs just as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The proof returned by `proof`.
+    /// * `data` - The original data of the leaf.
+    /// * `forest_root` - The expected forest root.
+    pub fn verify(&self, proof: &ForestProof, data: &[u8], forest_root: &str) -> bool {
+        let leaf_hash = self
+            .options
+            .leaf_hash(&self.hasher, proof.leaf_proof.leaf_index, data);
+        let tree_root = self.options.root_hash(
+            &self.hasher,
+            &fold_path(&self.hasher, &proof.leaf_proof, leaf_hash),
+            proof.leaf_count,
+        );
+
+        fold_path(&self.hasher, &proof.tree_proof, tree_root) == forest_root
+    }
+
+    /// Builds the tree over the roots of the forest, in name order.
+    fn roots_tree(&self) -> Option<MerkleTree> {
+        if self.trees.is_empty() {
+            return None;
+        }
+
+        Some(MerkleTree::from_leaf_hashes(
+            self.hasher.clone(),
+            self.trees.values().map(MerkleTree::root_hex),
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "sha256"))]
+mod tests {
+    use super::*;
+    use crate::hasher::SHA256Hasher;
+
+    #[test]
+    fn test_forest_root_commits_to_every_tree() {
+        let hasher = SHA256Hasher::new();
+        let mut forest = MerkleForest::new(hasher.clone());
+        assert!(forest.forest_root().is_none());
+
+        assert!(forest.insert("logs", ["a", "b", "c"]).is_none());
+        assert!(forest.insert("blobs", ["x", "y"]).is_none());
+        assert_eq!(forest.len(), 2);
+
+        let logs = MerkleTree::new(hasher.clone(), ["a", "b", "c"]);
+        let blobs = MerkleTree::new(hasher.clone(), ["x", "y"]);
+        assert_eq!(forest.root_of("logs"), Some(logs.root_hex()));
+        assert!(forest.root_of("missing").is_none());
+
+        // Roots are taken in name order: "blobs" before "logs".
+        let expected = hasher.combine(&blobs.root_hex(), &logs.root_hex());
+        assert_eq!(forest.forest_root(), Some(expected.clone()));
+
+        assert!(forest.insert("logs", ["a", "b", "d"]).is_some());
+        assert_ne!(forest.forest_root(), Some(expected));
+    }
+
+    #[test]
+    fn test_forest_proofs() {
+        let hasher = SHA256Hasher::new();
+        let options = TreeOptions::new().bind_index(true).commit_length(true);
+        let mut forest = MerkleForest::with_options(hasher, options);
+        forest.insert("a", ["1", "2", "3", "4", "5"]);
+        forest.insert("b", ["6"]);
+        forest.insert("c", ["7", "8"]);
+        let root = forest.forest_root().unwrap();
+
+        let proof = forest.proof("a", 4).unwrap();
+        assert_eq!(proof.tree_proof.leaf_index, 0);
+        assert!(forest.verify(&proof, b"5", &root));
+        assert!(!forest.verify(&proof, b"4", &root));
+
+        let proof = forest.proof("c", 1).unwrap();
+        assert_eq!(proof.tree_proof.leaf_index, 2);
+        assert!(forest.verify(&proof, b"8", &root));
+
+        let proof = forest.proof("b", 0).unwrap();
+        assert!(forest.verify(&proof, b"6", &root));
+
+        assert!(forest.proof("b", 1).is_none());
+        assert!(forest.proof("d", 0).is_none());
+    }
+}
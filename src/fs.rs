@@ -1,53 +1,1053 @@
 //! Provides the module used for filesystem operations made by this library.
 
-use std::path::Path;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, UNIX_EPOCH},
+};
 
-use crate::{hasher::Hasher, node::Node};
+use crate::{error::MerkleError, hasher::Hasher, merkletree::MerkleTree, node::Node};
+use rayon::prelude::*;
 
-/// Reads the entire content of a file into a `Vec<u8>`.
-///
-/// If the file cannot be read, an error message is printed to stderr, and the program exits.
+/// Domain prefix of the sentinel leaves emitted for empty directories.
+pub const EMPTY_DIR_DOMAIN: &[u8] = b"mt-rs:empty-dir\0";
+
+/// Domain prefix of the file leaves when empty directories get sentinels, so that no file
+/// content hashes to a sentinel.
+pub const FILE_DOMAIN: &[u8] = b"mt-rs:file\0";
+
+/// Largest sidecar file read by [`hash_dir_from_sidecars`], far above any hash line.
+const MAX_SIDECAR_SIZE: u64 = 4096;
+
+/// Options for [`hash_dir_with_options`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HashDirOptions<'a> {
+    /// Flag checked before each file; once set, the walk stops with `MerkleError::Cancelled`.
+    pub cancel: Option<&'a AtomicBool>,
+    /// Emit a sentinel leaf for each empty directory instead of skipping it.
+    pub empty_dirs: bool,
+    /// Largest file size in bytes that may be read; larger files fail with
+    /// `MerkleError::FileTooLarge`.
+    pub max_file_size: Option<u64>,
+}
+
+impl<'a> HashDirOptions<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the cancellation flag checked during the walk.
+    pub fn cancel(mut self, flag: &'a AtomicBool) -> Self {
+        self.cancel = Some(flag);
+        self
+    }
+
+    /// Emits a sentinel leaf for each empty directory, so the root commits to its presence.
+    ///
+    /// The sentinel is the hash of [`EMPTY_DIR_DOMAIN`] followed by the directory path relative
+    /// to the walked path it was found under, its components joined with `/`. The root is thus
+    /// the same whether a directory is walked as `dir`, `./dir` or an absolute path.
+    ///
+    /// File leaves are then the hash of [`FILE_DOMAIN`] followed by the content, instead of the
+    /// content alone, so that no file can produce the leaf of an empty directory.
+    pub fn empty_dirs(mut self, enabled: bool) -> Self {
+        self.empty_dirs = enabled;
+        self
+    }
+
+    /// Rejects files larger than `bytes` with `MerkleError::FileTooLarge` instead of reading
+    /// them.
+    ///
+    /// The size is checked from the metadata before reading, and reading stops as soon as the
+    /// limit is crossed, so neither a huge sparse file nor a file growing while it is read
+    /// can exhaust memory.
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Returns `Err(MerkleError::Cancelled)` if the cancellation flag is set.
+    pub(crate) fn check_cancelled(&self) -> Result<(), MerkleError> {
+        match self.cancel {
+            Some(flag) if flag.load(Ordering::Relaxed) => Err(MerkleError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Selects which file metadata fields are committed into a leaf together with its content.
+#[derive(Debug, Clone, Copy)]
+pub struct MetadataFields {
+    /// Commit the file size in bytes.
+    pub size: bool,
+    /// Commit the last modification time.
+    pub mtime: bool,
+}
+
+impl Default for MetadataFields {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl MetadataFields {
+    /// Commits both size and modification time.
+    pub fn all() -> Self {
+        Self {
+            size: true,
+            mtime: true,
+        }
+    }
+
+    /// Commits the size only, so touching a file without changing it keeps the same leaf.
+    pub fn without_mtime() -> Self {
+        Self {
+            size: true,
+            mtime: false,
+        }
+    }
+}
+
+/// Metadata collected for a single file by [`hash_dir_with_metadata`].
+#[derive(Debug, Clone)]
+pub struct FileMetadata {
+    /// Path of the file as visited by the walker.
+    pub path: PathBuf,
+    /// Hash of the file content alone.
+    pub content_hash: String,
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// Modification time as a duration since the Unix epoch, if requested and available.
+    pub mtime: Option<Duration>,
+}
+
+impl FileMetadata {
+    /// Returns the canonical encoding hashed into the leaf.
+    ///
+    /// The encoding is the content hash followed by the little-endian size (if selected) and
+    /// the little-endian modification time in nanoseconds (if selected).
+    pub fn canonical_bytes(&self, fields: MetadataFields) -> Vec<u8> {
+        let mut bytes = self.content_hash.as_bytes().to_vec();
+        if fields.size {
+            bytes.extend_from_slice(&self.size.to_le_bytes());
+        }
+        if fields.mtime {
+            let nanos = self.mtime.map(|mtime| mtime.as_nanos()).unwrap_or_default();
+            bytes.extend_from_slice(&nanos.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+/// Unwraps the result of a filesystem operation.
 ///
-/// `path` is a reference to a `String` representing the path to the file.
-fn read_file_content(path: &String) -> Vec<u8> {
-    match std::fs::read(path) {
-        Ok(contents) => contents,
+/// If it failed, an error message is printed to stderr, and the program exits.
+pub(crate) fn exit_on_error<T>(result: Result<T, MerkleError>) -> T {
+    match result {
+        Ok(value) => value,
         Err(e) => {
-            eprintln!("Failed to read file '{}': {}", path, e);
+            eprintln!("{}", e);
             std::process::exit(1);
         }
     }
 }
 
+/// Reads the entire content of a file into a `Vec<u8>`.
+///
+/// `path` is the path to the file. If `max_size` is given, larger files are rejected with
+/// `MerkleError::FileTooLarge`, at most `max_size + 1` bytes being read.
+fn read_file_content(path: &Path, max_size: Option<u64>) -> Result<Vec<u8>, MerkleError> {
+    let to_error = |source| MerkleError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let Some(max_size) = max_size else {
+        return std::fs::read(path).map_err(to_error);
+    };
+
+    let file = File::open(path).map_err(to_error)?;
+    let size = file.metadata().map_err(to_error)?.len();
+    if size > max_size {
+        return Err(MerkleError::FileTooLarge {
+            path: path.to_path_buf(),
+            size,
+        });
+    }
+
+    // The file may have grown since its metadata was read.
+    let mut content = Vec::with_capacity(size as usize);
+    file.take(max_size.saturating_add(1))
+        .read_to_end(&mut content)
+        .map_err(to_error)?;
+    if content.len() as u64 > max_size {
+        return Err(MerkleError::FileTooLarge {
+            path: path.to_path_buf(),
+            size: content.len() as u64,
+        });
+    }
+
+    Ok(content)
+}
+
+/// Reads the size and modification time of a file.
+///
+/// The modification time is `None` if the platform does not provide it.
+fn read_file_metadata(path: &Path) -> Result<(u64, Option<Duration>), MerkleError> {
+    let metadata = std::fs::metadata(path).map_err(|source| MerkleError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok());
+
+    Ok((metadata.len(), mtime))
+}
+
+/// Lists the entries of a directory in canonical order.
+///
+/// Entries are sorted by file name as raw bytes, case-sensitively, with files and
+/// subdirectories interleaved by name. The order depends neither on the filesystem nor on the
+/// platform's path comparison, so the same layout always yields the same root.
+fn read_dir_sorted(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut entries: Vec<PathBuf> = dir
+        .read_dir()?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+
+    entries.sort_by(|a, b| {
+        let name = |path: &PathBuf| {
+            path.file_name()
+                .map(|name| name.as_encoded_bytes().to_vec())
+        };
+        name(a).cmp(&name(b))
+    });
+
+    Ok(entries)
+}
+
 /// Recursively hashes the contents of files and directories.
 ///
 /// This function iterates through a list of filenames. For each file, it reads its content,
 /// hashes it using the provided `Hasher`, and creates a leaf `Node`. If an entry is a directory,
 /// it recursively calls itself to hash the directory's contents and extends the current
 /// list of nodes with the results.
-pub fn hash_dir<H>(hasher: H, filenames: Vec<String>) -> Vec<Node>
+///
+/// If a file or directory cannot be read, an error message is printed to stderr, and the
+/// program exits. Use [`hash_dir_with_options`] to handle the error instead.
+pub fn hash_dir<H, I, P>(hasher: H, filenames: I) -> Vec<Node>
 where
     H: Hasher + 'static + std::marker::Sync + Clone,
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
 {
-    let mut nodes: Vec<Node> = vec![];
-    for filename in &filenames {
-        let file = Path::new(filename);
-        if file.is_file() {
-            let hash = hasher.hash(read_file_content(filename).as_slice());
+    exit_on_error(hash_dir_with_options(
+        hasher,
+        filenames,
+        &HashDirOptions::default(),
+    ))
+}
 
-            nodes.push(Node::new_leaf(hash));
-        } else if file.is_dir() {
-            let mut filenames_in_dir: Vec<String> = file
-                .read_dir()
-                .unwrap()
-                .map(|entry| String::from(entry.unwrap().path().to_str().unwrap()))
+/// Recursively hashes the contents of files and directories like [`hash_dir`], returning an
+/// error instead of exiting when something cannot be read.
+///
+/// The cancellation flag of `options`, if any, is checked before each file.
+pub fn hash_dir_with_options<H, I, P>(
+    hasher: H,
+    filenames: I,
+    options: &HashDirOptions,
+) -> Result<Vec<Node>, MerkleError>
+where
+    H: Hasher + 'static + std::marker::Sync + Clone,
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let entries = hash_dir_with_paths(hasher, filenames, options)?;
+
+    Ok(entries.into_iter().map(|(_, node)| node).collect())
+}
+
+/// Recursively hashes files like [`hash_dir_with_options`], returning each leaf together with
+/// the path of the file it was computed from.
+pub fn hash_dir_with_paths<H, I, P>(
+    hasher: H,
+    filenames: I,
+    options: &HashDirOptions,
+) -> Result<Vec<(PathBuf, Node)>, MerkleError>
+where
+    H: Hasher + 'static + std::marker::Sync + Clone,
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    hash_dir_windowed(hasher, filenames, 1, options)
+}
+
+/// Recursively hashes files like [`hash_dir_with_paths`], reading and hashing up to
+/// `window_size` files at once in parallel.
+///
+/// The directories are walked first, without reading any file. Files are then processed in
+/// windows: only the leaf hashes and the contents of the current window are alive at any time,
+/// so peak memory is bounded by `window_size` times the largest file rather than by the total
+/// size. The result is identical for every window size.
+///
+/// The cancellation flag of `options`, if any, is checked before each window.
+pub fn hash_dir_windowed<H, I, P>(
+    hasher: H,
+    filenames: I,
+    window_size: usize,
+    options: &HashDirOptions,
+) -> Result<Vec<(PathBuf, Node)>, MerkleError>
+where
+    H: Hasher + 'static + std::marker::Sync + Clone,
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let walked = walk(filenames, options)?;
+
+    let mut entries = Vec::with_capacity(walked.len());
+    for window in walked.chunks(window_size.max(1)) {
+        options.check_cancelled()?;
+
+        let hashes: Vec<String> = window
+            .par_iter()
+            .map(|entry| match entry {
+                WalkEntry::File(path) => {
+                    let content = read_file_content(path, options.max_file_size)?;
+                    if !options.empty_dirs {
+                        return Ok(hasher.hash(&content));
+                    }
+                    let mut stream = hasher.stream();
+                    stream.update(FILE_DOMAIN);
+                    stream.update(&content);
+                    Ok(stream.finalize())
+                }
+                WalkEntry::EmptyDir { relative, .. } => {
+                    let mut stream = hasher.stream();
+                    stream.update(EMPTY_DIR_DOMAIN);
+                    stream.update(relative);
+                    Ok(stream.finalize())
+                }
+            })
+            .collect::<Result<_, MerkleError>>()?;
+
+        entries.extend(
+            window
+                .iter()
+                .zip(hashes)
+                .map(|(entry, hash)| (entry.path().to_path_buf(), Node::new_leaf(hash))),
+        );
+    }
+
+    Ok(entries)
+}
+
+/// An entry found by `walk`, to be hashed into a leaf.
+enum WalkEntry {
+    File(PathBuf),
+    /// An empty directory, only reported if `HashDirOptions::empty_dirs` is set.
+    EmptyDir {
+        path: PathBuf,
+        /// The path relative to the walked path it was found under, components joined with `/`.
+        relative: Vec<u8>,
+    },
+}
+
+impl WalkEntry {
+    fn path(&self) -> &Path {
+        match self {
+            WalkEntry::File(path) | WalkEntry::EmptyDir { path, .. } => path,
+        }
+    }
+}
+
+/// Returns `path` relative to `root`, its components joined with `/` whatever the platform.
+fn relative_path_bytes(path: &Path, root: &Path) -> Vec<u8> {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative
+        .components()
+        .map(|component| component.as_os_str().as_encoded_bytes())
+        .collect::<Vec<_>>()
+        .join(&b'/')
+}
+
+/// Recursively lists the files of `filenames`, in the order their leaves are built, without
+/// reading them.
+///
+/// Every path is checked upfront: each one must exist, each directory must be listable and
+/// each file must open. Nothing is hashed unless they all pass.
+///
+/// # Returns
+///
+/// The entries to hash, or `MerkleError::UnreadablePaths` listing every path that failed.
+fn walk<I, P>(filenames: I, options: &HashDirOptions) -> Result<Vec<WalkEntry>, MerkleError>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let mut entries = vec![];
+    let mut failures = vec![];
+    walk_into(filenames, None, options, &mut entries, &mut failures);
+
+    if failures.is_empty() {
+        Ok(entries)
+    } else {
+        Err(MerkleError::UnreadablePaths { failures })
+    }
+}
+
+/// Recursive step of `walk`, collecting failures instead of stopping at the first one.
+///
+/// `root` is the walked path `filenames` were found under, or `None` at the top level, where
+/// each path is its own root.
+fn walk_into<I, P>(
+    filenames: I,
+    root: Option<&Path>,
+    options: &HashDirOptions,
+    entries: &mut Vec<WalkEntry>,
+    failures: &mut Vec<(PathBuf, std::io::Error)>,
+) where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    for filename in filenames {
+        let file = filename.as_ref();
+        let metadata = match std::fs::metadata(file) {
+            Ok(metadata) => metadata,
+            Err(source) => {
+                failures.push((file.to_path_buf(), source));
+                continue;
+            }
+        };
+
+        if metadata.is_file() {
+            match File::open(file) {
+                Ok(_) => entries.push(WalkEntry::File(file.to_path_buf())),
+                Err(source) => failures.push((file.to_path_buf(), source)),
+            }
+        } else if metadata.is_dir() {
+            let filenames_in_dir = match read_dir_sorted(file) {
+                Ok(filenames_in_dir) => filenames_in_dir,
+                Err(source) => {
+                    failures.push((file.to_path_buf(), source));
+                    continue;
+                }
+            };
+            let root = root.unwrap_or(file);
+            if filenames_in_dir.is_empty() && options.empty_dirs {
+                entries.push(WalkEntry::EmptyDir {
+                    path: file.to_path_buf(),
+                    relative: relative_path_bytes(file, root),
+                });
+            }
+
+            walk_into(filenames_in_dir, Some(root), options, entries, failures);
+        }
+    }
+}
+
+/// Recursively builds leaves like [`hash_dir_with_paths`] from precomputed hashes, without
+/// reading the files themselves.
+///
+/// The leaf of each file is read from its sidecar, the file with the same name followed by
+/// `.` and `extension`, e.g. `blob.sha256` for `blob`. The sidecar holds the hex hash,
+/// optionally followed by whitespace and anything else, as written by `sha256sum`. Sidecars
+/// are not leaves themselves, and empty directories are skipped whatever `options` says.
+///
+/// # Arguments
+///
+/// * `filenames` - The files and directories to walk.
+/// * `extension` - The extension of the sidecars, without the leading dot.
+/// * `options` - The cancellation flag, checked before each file.
+///
+/// # Returns
+///
+/// The leaves with their paths, `MerkleError::Io` if a sidecar is missing or unreadable, or
+/// `MerkleError::InvalidSidecar` if it does not start with a hex hash.
+pub fn hash_dir_from_sidecars<I, P>(
+    filenames: I,
+    extension: &str,
+    options: &HashDirOptions,
+) -> Result<Vec<(PathBuf, Node)>, MerkleError>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let suffix = format!(".{extension}");
+    let walked = walk(filenames, options)?;
+
+    let mut entries = vec![];
+    for entry in walked {
+        let WalkEntry::File(path) = entry else {
+            continue;
+        };
+        if path
+            .as_os_str()
+            .as_encoded_bytes()
+            .ends_with(suffix.as_bytes())
+        {
+            continue;
+        }
+        options.check_cancelled()?;
+
+        let mut sidecar = path.clone().into_os_string();
+        sidecar.push(&suffix);
+        let sidecar = PathBuf::from(sidecar);
+        let content = read_file_content(&sidecar, Some(MAX_SIDECAR_SIZE))?;
+        let hash = std::str::from_utf8(&content)
+            .ok()
+            .and_then(|content| content.split_whitespace().next())
+            .filter(|hash| hex::decode(hash).is_ok_and(|bytes| !bytes.is_empty()))
+            .ok_or(MerkleError::InvalidSidecar {
+                path: sidecar.clone(),
+            })?;
+
+        entries.push((path, Node::new_leaf(hash.to_ascii_lowercase())));
+    }
+
+    Ok(entries)
+}
+
+/// A file that differs between a tree built from paths and the current filesystem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangedPath {
+    /// The file exists now but was not part of the old tree.
+    Added(PathBuf),
+    /// The file was part of the old tree but does not exist anymore.
+    Removed(PathBuf),
+    /// The file exists in both but its content changed.
+    Modified(PathBuf),
+}
+
+impl ChangedPath {
+    /// Returns the path of the changed file.
+    pub fn path(&self) -> &Path {
+        match self {
+            ChangedPath::Added(path) | ChangedPath::Removed(path) | ChangedPath::Modified(path) => {
+                path
+            }
+        }
+    }
+}
+
+/// Rehashes `root` and reports which files were added, removed or modified since `old_tree`
+/// was built.
+///
+/// `old_tree` must have been built with [`MerkleTree::from_paths`] (or
+/// [`MerkleTree::from_paths_with_options`]) over the same `root` and with the same hasher, so
+/// that its leaves are mapped to paths. If the rebuilt root matches the old one, no per-file
+/// comparison is done at all. If `root` holds no file anymore, every old file is reported as
+/// removed. The changes are sorted by path.
+pub fn tree_diff<H, P>(
+    old_tree: &MerkleTree,
+    root: P,
+    hasher: H,
+) -> Result<Vec<ChangedPath>, MerkleError>
+where
+    H: Hasher + 'static + std::marker::Sync + Clone,
+    P: AsRef<Path>,
+{
+    let new_tree = match MerkleTree::from_paths_with_options(
+        hasher,
+        [root.as_ref()],
+        &HashDirOptions::default(),
+    ) {
+        Ok(tree) => tree,
+        // Every file is gone, so there is no tree to compare with.
+        Err(MerkleError::EmptyInput) => {
+            let mut changes: Vec<ChangedPath> = old_tree
+                .paths()
+                .iter()
+                .cloned()
+                .map(ChangedPath::Removed)
                 .collect();
+            changes.sort_by(|a, b| a.path().cmp(b.path()));
+            return Ok(changes);
+        }
+        Err(e) => return Err(e),
+    };
+    if new_tree.root_hex() == old_tree.root_hex() {
+        return Ok(vec![]);
+    }
+
+    let old_entries: HashMap<&Path, &str> = old_tree
+        .paths()
+        .iter()
+        .map(PathBuf::as_path)
+        .zip(old_tree.leaves_ref().iter().map(Node::hash))
+        .collect();
+    let new_entries: HashMap<&Path, &str> = new_tree
+        .paths()
+        .iter()
+        .map(PathBuf::as_path)
+        .zip(new_tree.leaves_ref().iter().map(Node::hash))
+        .collect();
+
+    let mut changes: Vec<ChangedPath> = vec![];
+    for (path, hash) in &new_entries {
+        match old_entries.get(path) {
+            None => changes.push(ChangedPath::Added(path.to_path_buf())),
+            Some(old_hash) if old_hash != hash => {
+                changes.push(ChangedPath::Modified(path.to_path_buf()))
+            }
+            Some(_) => {}
+        }
+    }
+    for path in old_entries.keys() {
+        if !new_entries.contains_key(path) {
+            changes.push(ChangedPath::Removed(path.to_path_buf()));
+        }
+    }
+
+    changes.sort_by(|a, b| a.path().cmp(b.path()));
+
+    Ok(changes)
+}
+
+/// Recursively hashes files like [`hash_dir_with_options`], committing each leaf to selected
+/// file metadata.
+///
+/// Each leaf is the hash of [`FileMetadata::canonical_bytes`], that is the content hash combined
+/// with the fields enabled in `fields`. The metadata of every file is returned with its leaf.
+///
+/// Every path is checked before hashing anything, like [`hash_dir_with_paths`] does. The
+/// cancellation flag and the maximum file size of `options` are honoured, while empty
+/// directories are skipped whatever `options` says, having no metadata to commit to.
+///
+/// # Returns
+///
+/// The leaves with their metadata, `MerkleError::UnreadablePaths` listing every missing or
+/// unreadable path, or the error met while reading a file or its metadata.
+pub fn hash_dir_with_metadata<H, I, P>(
+    hasher: H,
+    filenames: I,
+    fields: MetadataFields,
+    options: &HashDirOptions,
+) -> Result<Vec<(Node, FileMetadata)>, MerkleError>
+where
+    H: Hasher,
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let walked = walk(filenames, options)?;
+
+    let mut entries = vec![];
+    for entry in walked {
+        let WalkEntry::File(path) = entry else {
+            continue;
+        };
+        options.check_cancelled()?;
+
+        let content_hash = hasher.hash(&read_file_content(&path, options.max_file_size)?);
+        let (size, mtime) = read_file_metadata(&path)?;
+
+        let metadata = FileMetadata {
+            path,
+            content_hash,
+            size,
+            mtime: if fields.mtime { mtime } else { None },
+        };
+        let hash = hasher.hash(&metadata.canonical_bytes(fields));
+
+        entries.push((Node::new_leaf(hash), metadata));
+    }
+
+    Ok(entries)
+}
+
+#[cfg(all(test, feature = "sha256"))]
+mod tests {
+    use super::*;
+    use crate::hasher::SHA256Hasher;
+
+    #[test]
+    fn test_hash_dir_with_metadata_commits_size() {
+        let hasher = SHA256Hasher::new();
+        let path = String::from("tests/pics/cubbit.png.enc.0");
+        let content = std::fs::read(&path).unwrap();
+
+        let entries = hash_dir_with_metadata(
+            hasher.clone(),
+            vec![path.clone()],
+            MetadataFields::without_mtime(),
+            &HashDirOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(entries.len(), 1);
+        let (leaf, metadata) = &entries[0];
+        assert_eq!(metadata.path, Path::new(&path));
+        assert_eq!(metadata.size, content.len() as u64);
+        assert!(metadata.mtime.is_none());
+        assert_eq!(metadata.content_hash, hasher.hash(&content));
+        assert_ne!(leaf.hash(), hash_dir(hasher, vec![path])[0].hash());
+    }
+
+    #[test]
+    fn test_hash_dir_with_metadata_on_directory() {
+        let entries = hash_dir_with_metadata(
+            SHA256Hasher::new(),
+            vec![String::from("tests/pics")],
+            MetadataFields::all(),
+            &HashDirOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|(_, metadata)| metadata.mtime.is_some()));
+    }
+
+    #[test]
+    fn test_hash_dir_with_metadata_reports_missing_paths() {
+        let missing = ["tests/pics/missing", "tests/missing"];
+        let result = hash_dir_with_metadata(
+            SHA256Hasher::new(),
+            ["tests/pics", missing[0], missing[1]],
+            MetadataFields::all(),
+            &HashDirOptions::default(),
+        );
+
+        let Err(MerkleError::UnreadablePaths { failures }) = result else {
+            panic!("missing paths must be reported");
+        };
+        let failed: Vec<&Path> = failures.iter().map(|(path, _)| path.as_path()).collect();
+        assert_eq!(failed, missing.map(Path::new));
+
+        let result = hash_dir_with_metadata(
+            SHA256Hasher::new(),
+            ["tests/pics/cubbit.png.enc.0"],
+            MetadataFields::all(),
+            &HashDirOptions::new().max_file_size(1),
+        );
+        assert!(matches!(result, Err(MerkleError::FileTooLarge { .. })));
+    }
+
+    #[test]
+    fn test_hash_dir_accepts_any_path_type() {
+        let hasher = SHA256Hasher::new();
+        let strings = vec![String::from("tests/pics")];
+        let paths = vec![PathBuf::from("tests/pics")];
+
+        let from_strings = hash_dir(hasher.clone(), strings);
+        let from_paths = hash_dir(hasher.clone(), &paths);
+        let from_strs = hash_dir(hasher, ["tests/pics"]);
+
+        assert_eq!(from_strings.len(), 3);
+        assert!(
+            from_strings
+                .iter()
+                .zip(&from_paths)
+                .zip(&from_strs)
+                .all(|((a, b), c)| a.hash() == b.hash() && b.hash() == c.hash())
+        );
+    }
+
+    #[test]
+    fn test_hash_dir_with_options_cancelled() {
+        let cancel = AtomicBool::new(true);
+        let options = HashDirOptions::new().cancel(&cancel);
+
+        let result = hash_dir_with_options(SHA256Hasher::new(), ["tests/pics"], &options);
+        assert!(matches!(result, Err(MerkleError::Cancelled)));
+
+        cancel.store(false, Ordering::Relaxed);
+        let nodes = hash_dir_with_options(SHA256Hasher::new(), ["tests/pics"], &options).unwrap();
+        assert_eq!(nodes.len(), 3);
+    }
+
+    #[test]
+    fn test_hash_dir_windowed_matches_sequential_walk() {
+        let hasher = SHA256Hasher::new();
+        let expected = hash_dir(hasher.clone(), ["tests/pics"]);
+
+        for window_size in [0, 1, 2, 3, 10] {
+            let entries = hash_dir_windowed(
+                hasher.clone(),
+                ["tests/pics"],
+                window_size,
+                &HashDirOptions::default(),
+            )
+            .unwrap();
+
+            assert_eq!(entries.len(), expected.len());
+            assert!(
+                entries
+                    .iter()
+                    .zip(&expected)
+                    .all(|((_, leaf), expected)| leaf.hash() == expected.hash())
+            );
+            assert_eq!(entries[2].0, Path::new("tests/pics/cubbit.png.enc.2"));
+        }
+    }
 
-            filenames_in_dir.sort();
+    #[test]
+    fn test_max_file_size() {
+        let size = std::fs::metadata("tests/pics/cubbit.png.enc.1")
+            .unwrap()
+            .len();
 
-            nodes.extend(hash_dir(hasher.clone(), filenames_in_dir));
+        let options = HashDirOptions::new().max_file_size(size);
+        let nodes = hash_dir_with_options(SHA256Hasher::new(), ["tests/pics"], &options);
+        assert_eq!(nodes.unwrap().len(), 3);
+
+        let options = HashDirOptions::new().max_file_size(size - 1);
+        match hash_dir_with_options(SHA256Hasher::new(), ["tests/pics"], &options) {
+            Err(MerkleError::FileTooLarge { path, size: actual }) => {
+                assert_eq!(actual, size);
+                assert_eq!(path, Path::new("tests/pics/cubbit.png.enc.0"));
+            }
+            _ => panic!("expected FileTooLarge"),
+        }
+    }
+
+    #[test]
+    fn test_hash_dir_from_sidecars() {
+        let hasher = SHA256Hasher::new();
+        let root = std::env::temp_dir().join(format!("mt-sidecars-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+
+        for name in ["a", "b"] {
+            let path = root.join(name);
+            std::fs::write(&path, name).unwrap();
+            let line = format!(
+                "{}  {}\n",
+                hasher.hash(name.as_bytes()).to_uppercase(),
+                name
+            );
+            std::fs::write(root.join(format!("{name}.sha256")), line).unwrap();
+        }
+
+        let entries = hash_dir_from_sidecars([&root], "sha256", &HashDirOptions::default());
+        let entries = entries.unwrap();
+        let expected = hash_dir_with_paths(
+            hasher.clone(),
+            [root.join("a"), root.join("b")],
+            &HashDirOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().zip(&expected).all(|(entry, expected)| {
+            entry.0 == expected.0 && entry.1.hash() == expected.1.hash()
+        }));
+
+        std::fs::write(root.join("b.sha256"), "not a hash").unwrap();
+        let result = hash_dir_from_sidecars([&root], "sha256", &HashDirOptions::default());
+        assert!(
+            matches!(result, Err(MerkleError::InvalidSidecar { path }) if path == root.join("b.sha256"))
+        );
+
+        std::fs::remove_file(root.join("b.sha256")).unwrap();
+        let result = hash_dir_from_sidecars([&root], "sha256", &HashDirOptions::default());
+        assert!(
+            matches!(result, Err(MerkleError::Io { path, .. }) if path == root.join("b.sha256"))
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_missing_paths_are_all_reported_before_hashing() {
+        let paths = ["tests/pics", "tests/missing-a", "tests/pics/missing-b"];
+        let cancel = AtomicBool::new(true);
+        let options = HashDirOptions::new().cancel(&cancel);
+
+        // The paths are checked before the cancellation flag, which is only read when hashing.
+        match hash_dir_with_options(SHA256Hasher::new(), paths, &options) {
+            Err(MerkleError::UnreadablePaths { failures }) => {
+                let failed: Vec<&Path> = failures.iter().map(|(path, _)| path.as_path()).collect();
+                assert_eq!(failed, [Path::new(paths[1]), Path::new(paths[2])]);
+                assert!(
+                    failures
+                        .iter()
+                        .all(|(_, error)| error.kind() == std::io::ErrorKind::NotFound)
+                );
+            }
+            _ => panic!("expected UnreadablePaths"),
         }
     }
 
-    nodes
+    #[test]
+    fn test_tree_diff() {
+        let root = std::env::temp_dir().join(format!("mt-tree-diff-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        std::fs::write(root.join("a"), b"a").unwrap();
+        std::fs::write(root.join("b"), b"b").unwrap();
+        std::fs::write(root.join("sub/c"), b"c").unwrap();
+
+        let hasher = SHA256Hasher::new();
+        let old_tree = MerkleTree::from_paths(hasher.clone(), [&root]);
+        assert_eq!(old_tree.paths().len(), 3);
+        assert!(
+            tree_diff(&old_tree, &root, hasher.clone())
+                .unwrap()
+                .is_empty()
+        );
+
+        std::fs::write(root.join("b"), b"changed").unwrap();
+        std::fs::remove_file(root.join("sub/c")).unwrap();
+        std::fs::write(root.join("sub/d"), b"d").unwrap();
+
+        let changes = tree_diff(&old_tree, &root, hasher).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            changes,
+            vec![
+                ChangedPath::Modified(root.join("b")),
+                ChangedPath::Removed(root.join("sub/c")),
+                ChangedPath::Added(root.join("sub/d")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tree_diff_of_emptied_directory() {
+        let root = std::env::temp_dir().join(format!("mt-tree-diff-empty-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        std::fs::write(root.join("a"), b"a").unwrap();
+        std::fs::write(root.join("sub/b"), b"b").unwrap();
+
+        let hasher = SHA256Hasher::new();
+        let old_tree = MerkleTree::from_paths(hasher.clone(), [&root]);
+
+        std::fs::remove_file(root.join("a")).unwrap();
+        std::fs::remove_file(root.join("sub/b")).unwrap();
+        let changes = tree_diff(&old_tree, &root, hasher).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            changes,
+            vec![
+                ChangedPath::Removed(root.join("a")),
+                ChangedPath::Removed(root.join("sub/b")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hash_dir_with_empty_dir_sentinel() {
+        let root = std::env::temp_dir().join(format!("mt-empty-dir-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("empty")).unwrap();
+        std::fs::write(root.join("a"), b"a").unwrap();
+
+        let hasher = SHA256Hasher::new();
+        let plain = hash_dir_with_paths(hasher.clone(), [&root], &HashDirOptions::new()).unwrap();
+        let with_sentinel = hash_dir_with_paths(
+            hasher.clone(),
+            [&root],
+            &HashDirOptions::new().empty_dirs(true),
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(plain.len(), 1);
+        assert_eq!(with_sentinel.len(), 2);
+        assert_eq!(with_sentinel[1].0, root.join("empty"));
+        assert_ne!(with_sentinel[1].1.hash(), hasher.hash(b""));
+        assert_eq!(
+            with_sentinel[1].1.hash(),
+            hasher.hash(&[EMPTY_DIR_DOMAIN, b"empty"].concat())
+        );
+        assert_eq!(
+            with_sentinel[0].1.hash(),
+            hasher.hash(&[FILE_DOMAIN, b"a"].concat())
+        );
+    }
+
+    #[test]
+    fn test_empty_dir_sentinel_ignores_how_the_root_is_named() {
+        let name = format!("mt-empty-dir-relative-{}", std::process::id());
+        let root = std::env::temp_dir().join(&name);
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("sub/empty")).unwrap();
+        std::fs::write(root.join("a"), b"a").unwrap();
+
+        let options = HashDirOptions::new().empty_dirs(true);
+        let leaves = |path: &Path| -> Vec<String> {
+            hash_dir_with_paths(SHA256Hasher::new(), [path], &options)
+                .unwrap()
+                .iter()
+                .map(|(_, node)| node.hash().to_string())
+                .collect()
+        };
+        let absolute = leaves(&root);
+        let dotted = leaves(&root.join(".").join("sub").join(".."));
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(absolute, dotted);
+        assert_eq!(
+            absolute[1],
+            SHA256Hasher::new().hash(&[EMPTY_DIR_DOMAIN, b"sub/empty"].concat())
+        );
+    }
+
+    #[test]
+    fn test_file_cannot_forge_empty_dir_sentinel() {
+        let root = std::env::temp_dir().join(format!("mt-empty-dir-forge-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("empty")).unwrap();
+        std::fs::create_dir_all(root.join("forged")).unwrap();
+        std::fs::write(
+            root.join("forged/empty"),
+            [EMPTY_DIR_DOMAIN, b"empty"].concat(),
+        )
+        .unwrap();
+
+        let options = HashDirOptions::new().empty_dirs(true);
+        let entries = hash_dir_with_paths(SHA256Hasher::new(), [&root], &options).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_ne!(entries[0].1.hash(), entries[1].1.hash());
+    }
+
+    #[test]
+    fn test_canonical_order_ignores_creation_order() {
+        let layout = ["b", "B", "a.txt", "a/z", "c/x"];
+        let roots: Vec<PathBuf> = [0, 1]
+            .iter()
+            .map(|run| {
+                let root =
+                    std::env::temp_dir().join(format!("mt-canonical-{}-{run}", std::process::id()));
+                let _ = std::fs::remove_dir_all(&root);
+                let mut files = layout.to_vec();
+                if *run == 1 {
+                    files.reverse();
+                }
+                for file in files {
+                    let path = root.join(file);
+                    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                    std::fs::write(&path, file).unwrap();
+                }
+                root
+            })
+            .collect();
+
+        let hasher = SHA256Hasher::new();
+        let trees: Vec<MerkleTree> = roots
+            .iter()
+            .map(|root| MerkleTree::from_paths(hasher.clone(), [root]))
+            .collect();
+        let relative: Vec<PathBuf> = trees[0]
+            .paths()
+            .iter()
+            .map(|path| path.strip_prefix(&roots[0]).unwrap().to_path_buf())
+            .collect();
+        for root in &roots {
+            std::fs::remove_dir_all(root).unwrap();
+        }
+
+        assert_eq!(trees[0].root_hex(), trees[1].root_hex());
+        assert_eq!(
+            relative,
+            ["B", "a/z", "a.txt", "b", "c/x"].map(PathBuf::from)
+        );
+    }
 }
@@ -1,6 +1,95 @@
 //! Provides hashing abstractions and implementations including SHA256 and a default dummy hasher.
+//!
+//! Each algorithm is gated behind its own feature (`sha256`, `keccak256` and `blake3`), all
+//! enabled by default.
 
-use sha2::Digest;
+use crate::error::ParseError;
+#[cfg(any(feature = "sha256", feature = "keccak256"))]
+use digest::Digest;
+use std::io::{self, Read};
+
+/// Size of the chunks read from a stream when hashing it incrementally.
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// SHA-256 of the empty input.
+#[cfg(feature = "sha256")]
+const SHA256_EMPTY: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+/// Keccak256 of the empty input.
+#[cfg(feature = "keccak256")]
+const KECCAK256_EMPTY: &str = "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
+
+/// Blake3 of the empty input.
+#[cfg(feature = "blake3")]
+const BLAKE3_EMPTY: &str = "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262";
+
+/// Input size from which Blake3 hashes across threads with the `blake3-simd` feature, below
+/// which the thread overhead outweighs the gain.
+#[cfg(feature = "blake3-simd")]
+const BLAKE3_RAYON_THRESHOLD: usize = 128 * 1024;
+
+/// Feeds `data` into `hasher`, across threads for large inputs with the `blake3-simd` feature.
+#[cfg(feature = "blake3")]
+fn blake3_update(hasher: &mut blake3::Hasher, data: &[u8]) {
+    #[cfg(feature = "blake3-simd")]
+    if data.len() >= BLAKE3_RAYON_THRESHOLD {
+        hasher.update_rayon(data);
+        return;
+    }
+    hasher.update(data);
+}
+
+/// An in-progress hash computation fed incrementally.
+pub trait HashStream: Send {
+    /// Feeds more bytes into the hash computation.
+    fn update(&mut self, data: &[u8]);
+
+    /// Consumes the state and returns the resulting hash as a hexadecimal string.
+    fn finalize(self: Box<Self>) -> String;
+}
+
+/// A stream for hashers without a native incremental API: bytes are buffered until
+/// `finalize`, which hashes them in one go.
+struct BufferedStream<'a, H: Hasher> {
+    hasher: &'a H,
+    buffer: Vec<u8>,
+}
+
+impl<H: Hasher> HashStream for BufferedStream<'_, H> {
+    fn update(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    fn finalize(self: Box<Self>) -> String {
+        self.hasher.hash(&self.buffer)
+    }
+}
+
+/// A stream backed by any RustCrypto `Digest`, hex-encoded on finalization.
+#[cfg(any(feature = "sha256", feature = "keccak256"))]
+struct DigestStream<D>(D);
+
+#[cfg(any(feature = "sha256", feature = "keccak256"))]
+impl<D: Digest + Send> HashStream for DigestStream<D> {
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(&mut self.0, data);
+    }
+
+    fn finalize(self: Box<Self>) -> String {
+        hex::encode(self.0.finalize())
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl HashStream for blake3::Hasher {
+    fn update(&mut self, data: &[u8]) {
+        blake3_update(self, data);
+    }
+
+    fn finalize(self: Box<Self>) -> String {
+        blake3::Hasher::finalize(&self).to_hex().to_string()
+    }
+}
 
 /// A trait representing a generic hash function.
 ///
@@ -8,6 +97,110 @@ use sha2::Digest;
 pub trait Hasher: Send + Sync {
     /// Hashes a sequence of bytes and returns the resulting hash as a hexadecimal string.
     fn hash(&self, input: &[u8]) -> String;
+
+    /// Combines the hashes of two children into the hash of their parent node.
+    ///
+    /// By default this is the hash of the concatenation of the two hexadecimal strings.
+    ///
+    /// Overriding it plugs any compression function into the trees and proofs of this crate,
+    /// such as an algebraic hash (Poseidon, Rescue, ...) for trees checked inside
+    /// zero-knowledge circuits. Trees and proofers never inspect the hashes, so a field-based
+    /// hasher only needs a canonical text encoding of its elements.
+    ///
+    /// `combine` must accept any pair of strings without panicking: while trees and proofers
+    /// only pass it outputs of `hash` or `combine`, verifiers pass it sibling hashes taken
+    /// straight from untrusted proofs. Map input that is not a canonical encoding to a value
+    /// that matches no real node, e.g. an empty string, so the proof is simply rejected.
+    ///
+    /// To also use the APIs handling raw digests,
+    /// such as `MerkleProof::to_bytes` or `FlatMerkleTree`, encode each element as the lowercase
+    /// hex of its canonical 32-byte big-endian representation, i.e. reduced modulo the field
+    /// order, so that each element has exactly one encoding.
+    fn combine(&self, left: &str, right: &str) -> String {
+        self.hash(&[left.as_bytes(), right.as_bytes()].concat())
+    }
+
+    /// Returns the hash of the empty input, `hash(&[])`, the constant that `HashEmpty` padding
+    /// and empty sparse subtrees are built from.
+    ///
+    /// Hashers with a well-known value override it with a constant.
+    fn empty_hash(&self) -> String {
+        self.hash(&[])
+    }
+
+    /// Returns the length in bytes of the digests returned by `hash` and `combine`, e.g. 32
+    /// for SHA-256, so callers handling raw digests do not have to assume one.
+    ///
+    /// By default this is the length of `empty_hash`.
+    fn output_len(&self) -> usize {
+        self.empty_hash().len() / 2
+    }
+
+    /// Returns a name identifying the hash function, e.g. `"sha256"`, so verifiers can check
+    /// they use the same algorithm as the tree.
+    ///
+    /// By default this is the Rust type name of the hasher.
+    fn algorithm_name(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+
+    /// Starts an incremental computation of the same hash returned by `hash`.
+    ///
+    /// The default implementation buffers the whole input; hashers backed by an incremental
+    /// digest override it to hash in constant memory.
+    fn stream(&self) -> Box<dyn HashStream + '_>
+    where
+        Self: Sized,
+    {
+        Box::new(BufferedStream {
+            hasher: self,
+            buffer: Vec::new(),
+        })
+    }
+}
+
+/// Hashes everything read from `reader` in chunks, without buffering the whole input when the
+/// hasher supports incremental hashing.
+pub fn hash_reader<H, R>(hasher: &H, mut reader: R) -> io::Result<String>
+where
+    H: Hasher,
+    R: Read,
+{
+    let mut stream = hasher.stream();
+    let mut buffer = vec![0u8; STREAM_CHUNK_SIZE];
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        stream.update(&buffer[..read]);
+    }
+
+    Ok(stream.finalize())
+}
+
+/// Hashes everything read from an asynchronous `reader` in chunks, like [`hash_reader`].
+#[cfg(feature = "tokio")]
+pub async fn hash_async_read<H, R>(hasher: &H, mut reader: R) -> io::Result<String>
+where
+    H: Hasher,
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncReadExt;
+
+    let mut stream = hasher.stream();
+    let mut buffer = vec![0u8; STREAM_CHUNK_SIZE];
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        stream.update(&buffer[..read]);
+    }
+
+    Ok(stream.finalize())
 }
 
 /// A dummy hasher used for testing or demonstration purposes.
@@ -23,81 +216,709 @@ impl Hasher for DummyHasher {
     }
 }
 
+#[cfg(feature = "sha256")]
 #[derive(Clone)]
 /// A hasher implementation using the SHA-256 cryptographic hash function.
 pub struct SHA256Hasher;
 
+#[cfg(feature = "sha256")]
 impl Default for SHA256Hasher {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[cfg(feature = "sha256")]
 impl SHA256Hasher {
     pub fn new() -> Self {
         Self {}
     }
 }
 
+#[cfg(feature = "sha256")]
 impl Hasher for SHA256Hasher {
     fn hash(&self, input: &[u8]) -> String {
         let mut hasher = sha2::Sha256::new();
         hasher.update(input);
         hex::encode(hasher.finalize())
     }
+
+    fn empty_hash(&self) -> String {
+        SHA256_EMPTY.to_string()
+    }
+
+    fn output_len(&self) -> usize {
+        32
+    }
+
+    fn algorithm_name(&self) -> String {
+        HashAlgorithm::Sha256.name().to_string()
+    }
+
+    fn stream(&self) -> Box<dyn HashStream + '_> {
+        Box::new(DigestStream(sha2::Sha256::new()))
+    }
 }
 
+#[cfg(feature = "keccak256")]
 #[derive(Clone)]
 /// A hasher implementation using the Keccak256 cryptographic hash function.
 pub struct Keccak256Hasher;
 
+#[cfg(feature = "keccak256")]
 impl Default for Keccak256Hasher {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[cfg(feature = "keccak256")]
 impl Keccak256Hasher {
     pub fn new() -> Self {
         Self {}
     }
 }
 
+#[cfg(feature = "keccak256")]
 impl Hasher for Keccak256Hasher {
     fn hash(&self, input: &[u8]) -> String {
         let mut hasher = sha3::Keccak256::new();
         hasher.update(input);
         hex::encode(hasher.finalize())
     }
+
+    fn empty_hash(&self) -> String {
+        KECCAK256_EMPTY.to_string()
+    }
+
+    fn output_len(&self) -> usize {
+        32
+    }
+
+    fn algorithm_name(&self) -> String {
+        HashAlgorithm::Keccak256.name().to_string()
+    }
+
+    fn stream(&self) -> Box<dyn HashStream + '_> {
+        Box::new(DigestStream(sha3::Keccak256::new()))
+    }
 }
 
+#[cfg(feature = "blake3")]
 #[derive(Clone)]
 /// A hasher implementation using the Blake3 cryptographic hash function.
 pub struct Blake3Hasher;
 
+#[cfg(feature = "blake3")]
 impl Default for Blake3Hasher {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[cfg(feature = "blake3")]
 impl Blake3Hasher {
     pub fn new() -> Self {
         Self {}
     }
 }
 
+#[cfg(feature = "blake3")]
 impl Hasher for Blake3Hasher {
     fn hash(&self, input: &[u8]) -> String {
         let mut hasher = blake3::Hasher::new();
-        hasher.update(input);
+        blake3_update(&mut hasher, input);
         hasher.finalize().to_hex().to_string()
     }
+
+    fn empty_hash(&self) -> String {
+        BLAKE3_EMPTY.to_string()
+    }
+
+    fn output_len(&self) -> usize {
+        32
+    }
+
+    fn algorithm_name(&self) -> String {
+        HashAlgorithm::Blake3.name().to_string()
+    }
+
+    fn stream(&self) -> Box<dyn HashStream + '_> {
+        Box::new(blake3::Hasher::new())
+    }
+}
+
+/// The hash algorithms shipped with this library, selectable at runtime.
+///
+/// Variants depend on the enabled features, so matches need a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HashAlgorithm {
+    /// SHA-256, as computed by `SHA256Hasher`.
+    #[cfg(feature = "sha256")]
+    Sha256,
+    /// Keccak256, as computed by `Keccak256Hasher`.
+    #[cfg(feature = "keccak256")]
+    Keccak256,
+    /// Blake3, as computed by `Blake3Hasher`.
+    #[cfg(feature = "blake3")]
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// Returns every algorithm enabled in this build.
+    pub fn all() -> &'static [HashAlgorithm] {
+        &[
+            #[cfg(feature = "sha256")]
+            HashAlgorithm::Sha256,
+            #[cfg(feature = "keccak256")]
+            HashAlgorithm::Keccak256,
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3,
+        ]
+    }
+
+    /// Returns the name of the algorithm.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            #[cfg(feature = "sha256")]
+            HashAlgorithm::Sha256 => "sha256",
+            #[cfg(feature = "keccak256")]
+            HashAlgorithm::Keccak256 => "keccak256",
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+}
+
+impl Hasher for HashAlgorithm {
+    #[cfg_attr(
+        not(any(feature = "sha256", feature = "keccak256", feature = "blake3")),
+        allow(unused_variables)
+    )]
+    fn hash(&self, input: &[u8]) -> String {
+        match *self {
+            #[cfg(feature = "sha256")]
+            HashAlgorithm::Sha256 => SHA256Hasher.hash(input),
+            #[cfg(feature = "keccak256")]
+            HashAlgorithm::Keccak256 => Keccak256Hasher.hash(input),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => Blake3Hasher.hash(input),
+        }
+    }
+
+    fn empty_hash(&self) -> String {
+        match *self {
+            #[cfg(feature = "sha256")]
+            HashAlgorithm::Sha256 => SHA256Hasher.empty_hash(),
+            #[cfg(feature = "keccak256")]
+            HashAlgorithm::Keccak256 => Keccak256Hasher.empty_hash(),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => Blake3Hasher.empty_hash(),
+        }
+    }
+
+    fn output_len(&self) -> usize {
+        match *self {
+            #[cfg(feature = "sha256")]
+            HashAlgorithm::Sha256 => SHA256Hasher.output_len(),
+            #[cfg(feature = "keccak256")]
+            HashAlgorithm::Keccak256 => Keccak256Hasher.output_len(),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => Blake3Hasher.output_len(),
+        }
+    }
+
+    fn algorithm_name(&self) -> String {
+        self.name().to_string()
+    }
+
+    fn stream(&self) -> Box<dyn HashStream + '_> {
+        match *self {
+            #[cfg(feature = "sha256")]
+            HashAlgorithm::Sha256 => SHA256Hasher.stream(),
+            #[cfg(feature = "keccak256")]
+            HashAlgorithm::Keccak256 => Keccak256Hasher.stream(),
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => Blake3Hasher.stream(),
+        }
+    }
+}
+
+#[derive(Clone)]
+/// A hasher that hashes leaves with one hash function and internal nodes with another.
+///
+/// `hash` is delegated to the leaf hasher while `combine` is delegated to the node hasher, so
+/// both `MerkleTree` and `DefaultProofer` use the right function at each level.
+///
+/// `empty_hash` is the leaf hasher's, as it stands for an empty leaf, while `output_len` is
+/// the node hasher's, the length of roots and internal nodes. Proof encodings with a single
+/// digest length thus need both hashers to have the same output length.
+pub struct DualHasher<L, N> {
+    leaf_hasher: L,
+    node_hasher: N,
+}
+
+impl<L, N> DualHasher<L, N>
+where
+    L: Hasher,
+    N: Hasher,
+{
+    pub fn new(leaf_hasher: L, node_hasher: N) -> Self {
+        Self {
+            leaf_hasher,
+            node_hasher,
+        }
+    }
+}
+
+impl<L, N> Hasher for DualHasher<L, N>
+where
+    L: Hasher,
+    N: Hasher,
+{
+    fn hash(&self, input: &[u8]) -> String {
+        self.leaf_hasher.hash(input)
+    }
+
+    fn combine(&self, left: &str, right: &str) -> String {
+        self.node_hasher.combine(left, right)
+    }
+
+    fn empty_hash(&self) -> String {
+        self.leaf_hasher.empty_hash()
+    }
+
+    fn output_len(&self) -> usize {
+        self.node_hasher.output_len()
+    }
+
+    fn stream(&self) -> Box<dyn HashStream + '_> {
+        self.leaf_hasher.stream()
+    }
+}
+
+#[derive(Clone)]
+/// A hasher that commits to data with two hash functions at once, for algorithm migrations.
+///
+/// The output is the concatenation of both digests, e.g. 64 bytes for SHA256 and Blake3.
+/// Concatenation is used rather than XOR or a truncated hash of both because it is robust: a
+/// collision of the composite is a collision of both inner functions, so the tree stays
+/// collision-resistant as long as either algorithm does. XOR only preserves pseudorandomness,
+/// and truncation brings the security back to a single 32-byte digest.
+pub struct CompositeHasher<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> CompositeHasher<A, B>
+where
+    A: Hasher,
+    B: Hasher,
+{
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+/// Feeds the same bytes to the streams of both hashers of a `CompositeHasher`.
+struct CompositeStream<'a> {
+    first: Box<dyn HashStream + 'a>,
+    second: Box<dyn HashStream + 'a>,
+}
+
+impl HashStream for CompositeStream<'_> {
+    fn update(&mut self, data: &[u8]) {
+        self.first.update(data);
+        self.second.update(data);
+    }
+
+    fn finalize(self: Box<Self>) -> String {
+        self.first.finalize() + &self.second.finalize()
+    }
+}
+
+impl<A, B> Hasher for CompositeHasher<A, B>
+where
+    A: Hasher,
+    B: Hasher,
+{
+    fn hash(&self, input: &[u8]) -> String {
+        self.first.hash(input) + &self.second.hash(input)
+    }
+
+    fn output_len(&self) -> usize {
+        self.first.output_len() + self.second.output_len()
+    }
+
+    fn stream(&self) -> Box<dyn HashStream + '_> {
+        Box::new(CompositeStream {
+            first: self.first.stream(),
+            second: self.second.stream(),
+        })
+    }
+}
+
+/// Reverses the byte order of a hex-encoded hash, e.g. to display it as Bitcoin does.
+///
+/// # Returns
+///
+/// The reversed hash in lowercase hex, or `ParseError::InvalidHex` if `hash` is not hex, e.g.
+/// if its length is odd.
+pub fn reverse_hex(hash: &str) -> Result<String, ParseError> {
+    let mut bytes = hex::decode(hash).map_err(|_| ParseError::InvalidHex)?;
+    bytes.reverse();
+    Ok(hex::encode(bytes))
+}
+
+/// Byte order in which hashes are emitted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// The order produced by the digest.
+    #[default]
+    Natural,
+    /// The digest bytes reversed, as when a hash is read as a little-endian integer.
+    Reversed,
+}
+
+#[derive(Clone)]
+/// A hasher emitting every hash, leaves, internal nodes and root alike, in a given byte order.
+///
+/// Internal nodes are still combined from the children in natural order, so a reversed tree
+/// holds exactly the reversed hashes of the natural one.
+pub struct ByteOrderHasher<H> {
+    hasher: H,
+    order: ByteOrder,
+}
+
+impl<H> ByteOrderHasher<H>
+where
+    H: Hasher,
+{
+    pub fn new(hasher: H, order: ByteOrder) -> Self {
+        Self { hasher, order }
+    }
+
+    /// Converts `hash` between natural order and the configured one.
+    ///
+    /// A hash that is not hex, e.g. a sibling of a tampered proof, becomes an empty string,
+    /// which matches no node.
+    fn apply(&self, hash: String) -> String {
+        match self.order {
+            ByteOrder::Natural => hash,
+            ByteOrder::Reversed => reverse_hex(&hash).unwrap_or_default(),
+        }
+    }
+}
+
+/// Converts the output of an inner stream to the configured byte order.
+struct ByteOrderStream<'a, H> {
+    hasher: &'a ByteOrderHasher<H>,
+    inner: Box<dyn HashStream + 'a>,
+}
+
+impl<H: Hasher> HashStream for ByteOrderStream<'_, H> {
+    fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    fn finalize(self: Box<Self>) -> String {
+        self.hasher.apply(self.inner.finalize())
+    }
+}
+
+impl<H> Hasher for ByteOrderHasher<H>
+where
+    H: Hasher,
+{
+    fn hash(&self, input: &[u8]) -> String {
+        self.apply(self.hasher.hash(input))
+    }
+
+    fn combine(&self, left: &str, right: &str) -> String {
+        let left = self.apply(left.to_string());
+        let right = self.apply(right.to_string());
+        if left.is_empty() || right.is_empty() {
+            return String::new();
+        }
+        self.apply(self.hasher.combine(&left, &right))
+    }
+
+    fn stream(&self) -> Box<dyn HashStream + '_> {
+        Box::new(ByteOrderStream {
+            hasher: self,
+            inner: self.hasher.stream(),
+        })
+    }
+}
+
+#[derive(Clone)]
+/// A hasher that prefixes every leaf with a salt before hashing it.
+///
+/// Internal nodes are combined by the inner hasher without salt.
+pub struct SaltedHasher<H> {
+    hasher: H,
+    salt: Vec<u8>,
+}
+
+impl<H> SaltedHasher<H>
+where
+    H: Hasher,
+{
+    pub fn new(hasher: H, salt: impl Into<Vec<u8>>) -> Self {
+        Self {
+            hasher,
+            salt: salt.into(),
+        }
+    }
+
+    /// Returns the salt prepended to every leaf.
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
+    }
 }
+
+impl<H> Hasher for SaltedHasher<H>
+where
+    H: Hasher,
+{
+    fn hash(&self, input: &[u8]) -> String {
+        self.hasher.hash(&[self.salt.as_slice(), input].concat())
+    }
+
+    fn combine(&self, left: &str, right: &str) -> String {
+        self.hasher.combine(left, right)
+    }
+
+    fn stream(&self) -> Box<dyn HashStream + '_> {
+        let mut stream = self.hasher.stream();
+        stream.update(&self.salt);
+        stream
+    }
+}
+
+#[derive(Clone)]
+/// A hasher whose combine is commutative: `combine(a, b) = H(a) + H(b)`, adding both digests
+/// as big-endian integers modulo `2^(8 * digest length)`.
+///
+/// The root is therefore invariant to the order of the two children of any node, without
+/// sorting them, and proofs verify whatever their direction flags say. Leaf updates work as
+/// with any other hasher. Use it with `CommutativeProofer`, or any proofer built on it.
+///
+/// **The root is only invariant to sibling swaps, not to reordering the leaves.** Swapping
+/// the two children of a node keeps the root, but moving a leaf to another pair does not,
+/// because the children are rehashed before being added. For a commitment independent of the
+/// order of the whole set, sort the leaves instead.
+///
+/// **An additive combine is far weaker than collision resistance.** Forging a node only
+/// requires values whose hashes sum to a target, a k-sum problem that Wagner's generalized
+/// birthday algorithm solves much faster than finding a collision of the inner hash, and
+/// ever faster as more candidate values are available. Only use it where nobody gains from
+/// forging a membership proof, never for commitments an adversary can attack.
+///
+/// This is an accumulator rather than a classic Merkle tree, and also gives up the binding of
+/// a proof to the position of the leaf: it only proves membership.
+///
+/// # Panics
+///
+/// `combine` panics if the inner hasher does not produce hex-encoded digests.
+pub struct CommutativeHasher<H> {
+    hasher: H,
+}
+
+impl<H> CommutativeHasher<H>
+where
+    H: Hasher,
+{
+    pub fn new(hasher: H) -> Self {
+        Self { hasher }
+    }
+}
+
+impl<H> Hasher for CommutativeHasher<H>
+where
+    H: Hasher,
+{
+    fn hash(&self, input: &[u8]) -> String {
+        self.hasher.hash(input)
+    }
+
+    fn combine(&self, left: &str, right: &str) -> String {
+        let decode = |hash: &str| {
+            hex::decode(self.hasher.hash(hash.as_bytes())).expect("hash is not hex-encoded")
+        };
+        let (mut sum, addend) = (decode(left), decode(right));
+
+        let mut carry = 0u16;
+        for (byte, other) in sum.iter_mut().zip(addend).rev() {
+            let total = *byte as u16 + other as u16 + carry;
+            *byte = total as u8;
+            carry = total >> 8;
+        }
+
+        hex::encode(sum)
+    }
+
+    fn stream(&self) -> Box<dyn HashStream + '_> {
+        self.hasher.stream()
+    }
+}
+
+#[derive(Clone)]
+/// A hasher keeping only the first `N` bytes of every digest of the inner hasher, leaves,
+/// internal nodes and root alike, to shrink proofs in proportion.
+///
+/// **Truncation trades security for size.** An `N`-byte digest only offers about `4 * N` bits
+/// of collision resistance: 16 bytes leave 64 bits, within reach of a motivated attacker able
+/// to forge a second dataset with the same root. Only use it where nobody gains from such a
+/// collision, such as integrity checks against accidental corruption, never for commitments
+/// to adversarial data.
+///
+/// Digests of the inner hasher shorter than `N` bytes are kept whole.
+pub struct TruncatingHasher<H, const N: usize> {
+    hasher: H,
+}
+
+impl<H, const N: usize> TruncatingHasher<H, N>
+where
+    H: Hasher,
+{
+    /// Wraps `hasher`. `N` must not be zero, which is rejected at compile time.
+    pub fn new(hasher: H) -> Self {
+        const { assert!(N > 0, "digests cannot be truncated to zero bytes") };
+        Self { hasher }
+    }
+
+    /// Keeps the first `N` bytes of a hex digest.
+    fn truncate(hash: String) -> String {
+        let mut hash = hash;
+        hash.truncate(2 * N);
+        hash
+    }
+}
+
+/// Truncates the output of an inner stream.
+struct TruncatingStream<'a, const N: usize> {
+    inner: Box<dyn HashStream + 'a>,
+}
+
+impl<const N: usize> HashStream for TruncatingStream<'_, N> {
+    fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    fn finalize(self: Box<Self>) -> String {
+        let mut hash = self.inner.finalize();
+        hash.truncate(2 * N);
+        hash
+    }
+}
+
+impl<H, const N: usize> Hasher for TruncatingHasher<H, N>
+where
+    H: Hasher,
+{
+    fn hash(&self, input: &[u8]) -> String {
+        Self::truncate(self.hasher.hash(input))
+    }
+
+    fn combine(&self, left: &str, right: &str) -> String {
+        Self::truncate(self.hasher.combine(left, right))
+    }
+
+    fn output_len(&self) -> usize {
+        N.min(self.hasher.output_len())
+    }
+
+    fn stream(&self) -> Box<dyn HashStream + '_> {
+        Box::new(TruncatingStream::<N> {
+            inner: self.hasher.stream(),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        merkletree::MerkleTree,
+        proof::{DefaultProofer, MerkleProof, Proofer},
+    };
+
+    /// A toy field-based hasher over the prime field of order `2^61 - 1`, with elements
+    /// encoded as 32-byte big-endian hex. Its combine is affine, so it is not collision
+    /// resistant: it only stands for an algebraic hash such as Poseidon.
+    #[derive(Clone)]
+    struct ToyFieldHasher;
+
+    impl ToyFieldHasher {
+        const ORDER: u128 = (1 << 61) - 1;
+
+        /// Decodes a canonical element, or returns `None` for any other string.
+        fn decode(element: &str) -> Option<u128> {
+            let bytes = hex::decode(element)
+                .ok()
+                .filter(|bytes| bytes.len() == 32)?;
+            let value = u128::from_be_bytes(bytes[16..].try_into().unwrap());
+            (bytes[..16].iter().all(|b| *b == 0) && value < Self::ORDER).then_some(value)
+        }
+
+        fn encode(value: u128) -> String {
+            hex::encode([[0; 16], (value % Self::ORDER).to_be_bytes()].concat())
+        }
+    }
+
+    impl Hasher for ToyFieldHasher {
+        fn hash(&self, input: &[u8]) -> String {
+            Self::encode(
+                input
+                    .iter()
+                    .fold(0, |acc, b| (acc * 257 + *b as u128) % Self::ORDER),
+            )
+        }
+
+        fn combine(&self, left: &str, right: &str) -> String {
+            match (Self::decode(left), Self::decode(right)) {
+                (Some(left), Some(right)) => Self::encode(3 * left + 5 * right + 7),
+                // Not canonical, e.g. from a tampered proof: matches no node.
+                _ => String::new(),
+            }
+        }
+    }
 
+    #[test]
+    fn test_field_combine_drives_trees_and_proofs() {
+        let hasher = ToyFieldHasher;
+        let data = ["a", "b", "c", "d", "e"];
+        let tree = MerkleTree::new(hasher.clone(), data);
+        let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+        let root = tree.root_hex();
+        assert_eq!(
+            ToyFieldHasher::encode(ToyFieldHasher::decode(&root).unwrap()),
+            root
+        );
+
+        for (index, item) in data.iter().enumerate() {
+            let proof = proofer.generate(index).unwrap();
+            assert!(proofer.verify(&proof, item, &root));
+            assert_eq!(
+                MerkleProof::from_bytes(&proof.to_bytes()).unwrap().path,
+                proof.path
+            );
+        }
+
+        let (a, b) = (hasher.hash(b"a"), hasher.hash(b"b"));
+        assert_eq!(tree.level_hashes(1).unwrap()[0], hasher.combine(&a, &b));
+
+        // Siblings from an untrusted proof may not be canonical elements.
+        let mut proof = proofer.generate(0).unwrap();
+        for forged in ["not hex", "00", &"ff".repeat(32)] {
+            proof.path[0].hash = forged.to_string();
+            assert!(!proofer.verify(&proof, "a", &root));
+        }
+    }
+
+    #[cfg(feature = "sha256")]
     #[test]
     fn test_sha256_hasher_with_known_input() {
         let hasher = SHA256Hasher;
@@ -107,6 +928,7 @@ mod tests {
         assert_eq!(actual_hash, expected_hash);
     }
 
+    #[cfg(feature = "sha256")]
     #[test]
     fn test_sha256_hasher_empty_string() {
         let hasher = SHA256Hasher;
@@ -114,8 +936,10 @@ mod tests {
         let expected_hash = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
         let actual_hash = hasher.hash(input);
         assert_eq!(actual_hash, expected_hash);
+        assert_eq!(hasher.empty_hash(), expected_hash);
     }
 
+    #[cfg(feature = "keccak256")]
     #[test]
     fn test_keccak256_hasher_with_known_input() {
         let hasher = Keccak256Hasher;
@@ -125,6 +949,7 @@ mod tests {
         assert_eq!(actual_hash, expected_hash);
     }
 
+    #[cfg(feature = "keccak256")]
     #[test]
     fn test_keccak256_hasher_empty_string() {
         let hasher = Keccak256Hasher;
@@ -132,8 +957,10 @@ mod tests {
         let expected_hash = "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
         let actual_hash = hasher.hash(input);
         assert_eq!(actual_hash, expected_hash);
+        assert_eq!(hasher.empty_hash(), expected_hash);
     }
 
+    #[cfg(feature = "blake3")]
     #[test]
     fn test_blake3_hasher_with_known_input() {
         let hasher = Blake3Hasher;
@@ -143,6 +970,7 @@ mod tests {
         assert_eq!(actual_hash, expected_hash);
     }
 
+    #[cfg(feature = "blake3")]
     #[test]
     fn test_blake3_hasher_empty_string() {
         let hasher = Blake3Hasher;
@@ -150,5 +978,211 @@ mod tests {
         let expected_hash = "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262";
         let actual_hash = hasher.hash(input);
         assert_eq!(actual_hash, expected_hash);
+        assert_eq!(hasher.empty_hash(), expected_hash);
+    }
+
+    #[cfg(all(feature = "blake3", feature = "sha256"))]
+    #[test]
+    fn test_dual_hasher_splits_leaf_and_node_hashing() {
+        let hasher = DualHasher::new(SHA256Hasher, Blake3Hasher);
+
+        assert_eq!(hasher.hash(b"hello"), SHA256Hasher.hash(b"hello"));
+        assert_eq!(hasher.combine("ab", "cd"), Blake3Hasher.hash(b"abcd"));
+
+        let hasher = DualHasher::new(
+            SHA256Hasher,
+            CompositeHasher::new(SHA256Hasher, Blake3Hasher),
+        );
+        assert_eq!(hasher.empty_hash(), SHA256_EMPTY);
+        assert_eq!(hasher.output_len(), 64);
+    }
+
+    #[cfg(all(feature = "blake3", feature = "keccak256", feature = "sha256"))]
+    #[test]
+    fn test_hash_reader_matches_hash() {
+        let input: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+
+        assert_eq!(
+            hash_reader(&SHA256Hasher, input.as_slice()).unwrap(),
+            SHA256Hasher.hash(&input)
+        );
+        assert_eq!(
+            hash_reader(&Keccak256Hasher, input.as_slice()).unwrap(),
+            Keccak256Hasher.hash(&input)
+        );
+        assert_eq!(
+            hash_reader(&Blake3Hasher, input.as_slice()).unwrap(),
+            Blake3Hasher.hash(&input)
+        );
+        assert_eq!(
+            hash_reader(&DummyHasher, input.as_slice()).unwrap(),
+            DummyHasher.hash(&input)
+        );
+    }
+
+    #[cfg(all(feature = "tokio", feature = "blake3"))]
+    #[tokio::test]
+    async fn test_hash_async_read_matches_hash() {
+        let input: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+
+        assert_eq!(
+            hash_async_read(&Blake3Hasher, input.as_slice())
+                .await
+                .unwrap(),
+            Blake3Hasher.hash(&input)
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_salted_hasher_prefixes_leaves_only() {
+        let hasher = SaltedHasher::new(SHA256Hasher, b"salt".to_vec());
+
+        assert_eq!(hasher.hash(b"hello"), SHA256Hasher.hash(b"salthello"));
+        assert_eq!(hasher.combine("ab", "cd"), SHA256Hasher.combine("ab", "cd"));
+        assert_eq!(
+            hash_reader(&hasher, "hello".as_bytes()).unwrap(),
+            hasher.hash(b"hello")
+        );
+    }
+
+    #[cfg(all(feature = "sha256", feature = "blake3"))]
+    #[test]
+    fn test_composite_hasher_concatenates_digests() {
+        let hasher = CompositeHasher::new(SHA256Hasher, Blake3Hasher);
+        let hash = hasher.hash(b"hello");
+
+        assert_eq!(hash.len(), 128);
+        assert_eq!(hasher.output_len(), 64);
+        assert_eq!(hash[..64], SHA256Hasher.hash(b"hello"));
+        assert_eq!(hash[64..], Blake3Hasher.hash(b"hello"));
+        assert_eq!(hash_reader(&hasher, "hello".as_bytes()).unwrap(), hash);
+    }
+
+    #[test]
+    fn test_reverse_hex() {
+        assert_eq!(reverse_hex("0a1b2c").unwrap(), "2c1b0a");
+        assert_eq!(reverse_hex("0A1B").unwrap(), "1b0a");
+        assert_eq!(reverse_hex("").unwrap(), "");
+        assert_eq!(reverse_hex("abc"), Err(ParseError::InvalidHex));
+        assert_eq!(reverse_hex("zz"), Err(ParseError::InvalidHex));
+        assert_eq!(reverse_hex("\u{e9}\u{e9}"), Err(ParseError::InvalidHex));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_byte_order_hasher_reverses_every_hash() {
+        use crate::merkletree::MerkleTree;
+
+        let data = ["a", "b", "c"];
+        let natural = MerkleTree::new(SHA256Hasher, data);
+        let hasher = ByteOrderHasher::new(SHA256Hasher, ByteOrder::Reversed);
+        let reversed = MerkleTree::new(hasher.clone(), data);
+
+        assert_eq!(
+            reversed.root_hex(),
+            reverse_hex(&natural.root_hex()).unwrap()
+        );
+        assert_eq!(
+            hasher.hash(b"a"),
+            reverse_hex(&SHA256Hasher.hash(b"a")).unwrap()
+        );
+        assert_eq!(hasher.combine("not hex", &hasher.hash(b"a")), "");
+        assert_eq!(
+            hash_reader(&hasher, "a".as_bytes()).unwrap(),
+            hasher.hash(b"a")
+        );
+
+        let unchanged = ByteOrderHasher::new(SHA256Hasher, ByteOrder::Natural);
+        assert_eq!(
+            MerkleTree::new(unchanged, data).root_hex(),
+            natural.root_hex()
+        );
+    }
+
+    // Known answers computed with portable implementations: they must hold whatever backend
+    // the `sha2-asm` and `blake3-simd` features select.
+    #[cfg(any(feature = "sha256", feature = "blake3"))]
+    fn accelerated_input() -> Vec<u8> {
+        (0..1u32 << 20).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_sha256_digest_independent_of_backend() {
+        assert_eq!(
+            SHA256Hasher.hash(&accelerated_input()),
+            "631b84027d6b9e52b539c4e8373622d23032dfadc64d60af87339c9037e4f769"
+        );
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_blake3_digest_independent_of_backend() {
+        assert_eq!(
+            Blake3Hasher.hash(&accelerated_input()),
+            "74cb441fd087764ca9c3694da742ebe30cbeb3060a17009ca81825c7a8d10343"
+        );
+    }
+
+    #[cfg(feature = "blake3-simd")]
+    #[test]
+    fn test_blake3_multithreaded_digest_matches_single_threaded() {
+        let input = accelerated_input();
+        let single_threaded = blake3::Hasher::new().update(&input).finalize();
+        assert_eq!(
+            Blake3Hasher.hash(&input),
+            single_threaded.to_hex().to_string()
+        );
+
+        let mut stream = Blake3Hasher.stream();
+        stream.update(&input[..1000]);
+        stream.update(&input[1000..]);
+        assert_eq!(stream.finalize(), single_threaded.to_hex().to_string());
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_commutative_hasher() {
+        let hasher = CommutativeHasher::new(SHA256Hasher::new());
+        let (a, b) = (hasher.hash(b"a"), hasher.hash(b"b"));
+        assert_eq!(hasher.combine(&a, &b), hasher.combine(&b, &a));
+        assert_ne!(hasher.combine(&a, &b), hasher.combine(&a, &a));
+
+        // Carries propagate across bytes and the sum wraps around.
+        let inner = SHA256Hasher::new();
+        let sum = hex::decode(hasher.combine(&a, &b)).unwrap();
+        let (x, y) = (
+            hex::decode(inner.hash(a.as_bytes())).unwrap(),
+            hex::decode(inner.hash(b.as_bytes())).unwrap(),
+        );
+        let low = |bytes: &[u8]| u128::from_be_bytes(bytes[16..].try_into().unwrap());
+        assert_eq!(low(&sum), low(&x).wrapping_add(low(&y)));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_truncating_hasher() {
+        let hasher = TruncatingHasher::<_, 16>::new(SHA256Hasher::new());
+        let full = SHA256Hasher::new().hash(b"hello");
+        assert_eq!(hasher.hash(b"hello"), full[..32]);
+
+        let (a, b) = (hasher.hash(b"a"), hasher.hash(b"b"));
+        assert_eq!(
+            hasher.combine(&a, &b),
+            SHA256Hasher::new().combine(&a, &b)[..32]
+        );
+
+        let mut stream = hasher.stream();
+        stream.update(b"hel");
+        stream.update(b"lo");
+        assert_eq!(stream.finalize(), hasher.hash(b"hello"));
+
+        // Digests shorter than N bytes are kept whole.
+        let long = TruncatingHasher::<_, 64>::new(SHA256Hasher::new());
+        assert_eq!(long.hash(b"hello"), full);
+
+        assert_eq!(hasher.output_len(), 16);
+        assert_eq!(long.output_len(), 32);
     }
 }
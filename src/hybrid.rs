@@ -0,0 +1,182 @@
+//! Provides proofs checked under two hash algorithms at once, for archives that must stay
+//! verifiable even if one of the algorithms is broken.
+//!
+//! The same data is committed to by two trees, one per algorithm, each with its own root. A
+//! hybrid proof holds a proof of each tree, and only verifies if both lead to their root, so
+//! forging one requires breaking both algorithms.
+
+use crate::{
+    hasher::Hasher,
+    merkletree::{MerkleTree, MerkleTreeBuilder, TreeOptions},
+    proof::{DefaultProofer, MerkleProof, Proofer},
+};
+
+/// A proof of the same leaf in the trees of both algorithms of a `HybridProofer`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HybridProof {
+    /// The proof of the leaf in the tree of the first algorithm.
+    pub first: MerkleProof,
+    /// The proof of the leaf in the tree of the second algorithm.
+    pub second: MerkleProof,
+}
+
+/// A proofer over two trees of the same data, built with two different hashers.
+pub struct HybridProofer<A: Hasher, B: Hasher> {
+    first: DefaultProofer<A>,
+    second: DefaultProofer<B>,
+    first_root: String,
+    second_root: String,
+}
+
+impl<A, B> HybridProofer<A, B>
+where
+    A: Hasher + 'static + std::marker::Sync + Clone,
+    B: Hasher + 'static + std::marker::Sync + Clone,
+{
+    /// Builds the trees of both hashers over `data`, with the default options.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    pub fn new<T>(first: A, second: B, data: &[T]) -> Self
+    where
+        T: AsRef<[u8]>,
+    {
+        Self::with_options(first, second, TreeOptions::default(), data)
+    }
+
+    /// Builds the trees of both hashers over `data`, with the same `options`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    pub fn with_options<T>(first: A, second: B, options: TreeOptions, data: &[T]) -> Self
+    where
+        T: AsRef<[u8]>,
+    {
+        let first_tree = MerkleTreeBuilder::with_options(first.clone(), options).build(data);
+        let second_tree = MerkleTreeBuilder::with_options(second.clone(), options).build(data);
+        Self::from_trees(first, &first_tree, second, &second_tree)
+    }
+
+    /// Creates the proofer from two trees built over the same data.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The hasher `first_tree` was built with.
+    /// * `first_tree` - The tree of the first algorithm.
+    /// * `second` - The hasher `second_tree` was built with.
+    /// * `second_tree` - The tree of the second algorithm.
+    pub fn from_trees(
+        first: A,
+        first_tree: &MerkleTree,
+        second: B,
+        second_tree: &MerkleTree,
+    ) -> Self {
+        Self {
+            first: DefaultProofer::from_tree(first, first_tree),
+            second: DefaultProofer::from_tree(second, second_tree),
+            first_root: first_tree.root_hex(),
+            second_root: second_tree.root_hex(),
+        }
+    }
+
+    /// Returns the roots of the trees of the first and second algorithms.
+    pub fn roots(&self) -> (&str, &str) {
+        (&self.first_root, &self.second_root)
+    }
+
+    /// Generates the proofs of the leaf at `index` in both trees.
+    ///
+    /// # Returns
+    ///
+    /// The hybrid proof, or `None` if `index` is not a leaf.
+    pub fn generate(&self, index: usize) -> Option<HybridProof> {
+        Some(HybridProof {
+            first: self.first.generate(index)?,
+            second: self.second.generate(index)?,
+        })
+    }
+
+    /// Verifies that `data` is the leaf proven by `proof` under both roots.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The proof returned by `generate`.
+    /// * `data` - The original data of the leaf.
+    /// * `first_root` - The expected root of the tree of the first algorithm.
+    /// * `second_root` - The expected root of the tree of the second algorithm.
+    ///
+    /// # Returns
+    ///
+    /// `true` only if both proofs are for the same leaf index, each matching the index implied
+    /// by its path directions, and each leads to its root.
+    pub fn verify<T>(
+        &self,
+        proof: &HybridProof,
+        data: T,
+        first_root: &str,
+        second_root: &str,
+    ) -> bool
+    where
+        T: AsRef<[u8]>,
+    {
+        let data = data.as_ref();
+        proof.first.leaf_index == proof.second.leaf_index
+            && self.first.verify_strict(&proof.first, data, first_root)
+            && self.second.verify_strict(&proof.second, data, second_root)
+    }
+}
+
+#[cfg(all(test, feature = "sha256", feature = "blake3"))]
+mod tests {
+    use super::*;
+    use crate::hasher::{Blake3Hasher, SHA256Hasher};
+
+    #[test]
+    fn test_hybrid_proofs_need_both_roots() {
+        let data = ["a", "b", "c", "d", "e"];
+        let proofer = HybridProofer::new(SHA256Hasher, Blake3Hasher, &data);
+        let (first_root, second_root) = proofer.roots();
+        assert_eq!(first_root, MerkleTree::new(SHA256Hasher, data).root_hex());
+        assert_eq!(second_root, MerkleTree::new(Blake3Hasher, data).root_hex());
+
+        for (index, item) in data.iter().enumerate() {
+            let proof = proofer.generate(index).unwrap();
+            assert!(proofer.verify(&proof, item, first_root, second_root));
+            assert!(!proofer.verify(&proof, "x", first_root, second_root));
+            assert!(!proofer.verify(&proof, item, first_root, first_root));
+        }
+        assert!(proofer.generate(5).is_none());
+
+        // A valid proof of another leaf in one of the trees is rejected.
+        let mut proof = proofer.generate(0).unwrap();
+        proof.second = proofer.generate(1).unwrap().second;
+        assert!(!proofer.verify(&proof, "a", first_root, second_root));
+    }
+
+    #[test]
+    fn test_hybrid_proofs_reject_relabeled_index() {
+        let data = ["a", "b", "a", "d"];
+        let proofer = HybridProofer::new(SHA256Hasher, Blake3Hasher, &data);
+        let (first_root, second_root) = proofer.roots();
+
+        // The second half proves the "a" at index 2, relabeled as index 0.
+        let mut proof = proofer.generate(0).unwrap();
+        proof.second = proofer.generate(2).unwrap().second;
+        proof.second.leaf_index = 0;
+        assert!(!proofer.verify(&proof, "a", first_root, second_root));
+    }
+
+    #[test]
+    fn test_hybrid_proofs_respect_options() {
+        let data = ["1", "2", "3"];
+        let options = TreeOptions::new().bind_index(true).commit_length(true);
+        let proofer = HybridProofer::with_options(SHA256Hasher, Blake3Hasher, options, &data);
+        let (first_root, second_root) = proofer.roots();
+
+        let proof = proofer.generate(2).unwrap();
+        assert!(proofer.verify(&proof, "3", first_root, second_root));
+        assert_ne!(first_root, MerkleTree::new(SHA256Hasher, data).root_hex());
+    }
+}
@@ -0,0 +1,213 @@
+//! Provides an append-only Merkle tree whose root can be read after every append, for live
+//! accumulators such as the commitment of a growing log.
+//!
+//! Only the frontier is stored: at each level, the root of the last complete subtree still
+//! waiting for its right sibling. Both appending a leaf and computing the root take O(log n).
+
+use crate::{hasher::Hasher, merkletree::TreeOptions};
+
+/// An append-only Merkle tree storing only its frontier.
+///
+/// After each `push`, `root` returns the same root as `MerkleTree` built with the same options
+/// over all the leaves pushed so far.
+pub struct IncrementalMerkleTree<H: Hasher> {
+    hasher: H,
+    options: TreeOptions,
+    /// Constant hash odd levels are padded with, if not duplicating their last node.
+    padding_hash: Option<String>,
+    frontier: Frontier,
+}
+
+/// The roots of the complete subtrees of a tree still waiting for their right sibling, shared
+/// by `IncrementalMerkleTree` and append proofs.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Frontier {
+    /// Root of the pending complete subtree of each level, if any: level `l` has one exactly
+    /// when bit `l` of `len` is set.
+    levels: Vec<Option<String>>,
+    len: usize,
+}
+
+impl Frontier {
+    /// Creates the frontier of a tree of `len` leaves from the roots of its pending subtrees,
+    /// lowest level first.
+    ///
+    /// # Returns
+    ///
+    /// The frontier, or `None` if there is not exactly one root per set bit of `len`.
+    pub(crate) fn from_roots(len: usize, roots: &[String]) -> Option<Self> {
+        if roots.len() != len.count_ones() as usize {
+            return None;
+        }
+
+        let mut roots = roots.iter();
+        let levels = (0..usize::BITS - len.leading_zeros())
+            .map(|level| (len >> level & 1 == 1).then(|| roots.next().unwrap().clone()))
+            .collect();
+        Some(Self { levels, len })
+    }
+
+    /// Appends a leaf hash, merging every completed pair into its parent.
+    pub(crate) fn push<H: Hasher>(&mut self, hasher: &H, hash: String) {
+        let mut hash = hash;
+        let mut level = 0;
+        // Every completed pair is merged into its parent, like a carry in binary addition.
+        while let Some(left) = self.levels.get_mut(level).and_then(Option::take) {
+            hash = hasher.combine(&left, &hash);
+            level += 1;
+        }
+        if level == self.levels.len() {
+            self.levels.push(None);
+        }
+        self.levels[level] = Some(hash);
+        self.len += 1;
+    }
+
+    /// Returns the root over the leaves pushed so far, in O(log n), or `None` if there are
+    /// none.
+    pub(crate) fn root<H: Hasher>(
+        &self,
+        hasher: &H,
+        options: &TreeOptions,
+        padding_hash: Option<&str>,
+    ) -> Option<String> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let pad = |last: &str| hasher.combine(last, padding_hash.unwrap_or(last));
+
+        // `carry` is the last, incomplete node of the current level, if the last complete
+        // subtree of the level below was already merged.
+        let mut carry: Option<String> = None;
+        let mut count = self.len;
+        let mut level = 0;
+        while count > 1 {
+            let pending = self.levels[level].as_deref();
+            carry = match (carry, count % 2 == 1) {
+                (None, false) => None,
+                (None, true) => Some(pad(pending.expect("pending subtree"))),
+                (Some(last), true) => Some(pad(&last)),
+                (Some(last), false) => {
+                    Some(hasher.combine(pending.expect("pending left sibling"), &last))
+                }
+            };
+            count = count.div_ceil(2);
+            level += 1;
+        }
+
+        let root = carry.unwrap_or_else(|| {
+            self.levels[level]
+                .clone()
+                .expect("complete tree has a pending root")
+        });
+        Some(options.root_hash(hasher, &root, self.len))
+    }
+}
+
+impl<H> IncrementalMerkleTree<H>
+where
+    H: Hasher,
+{
+    /// Creates an empty tree using `hasher` and the default options.
+    pub fn new(hasher: H) -> Self {
+        Self::with_options(hasher, TreeOptions::default())
+    }
+
+    /// Creates an empty tree using `hasher` and `options`.
+    pub fn with_options(hasher: H, options: TreeOptions) -> Self {
+        Self {
+            padding_hash: options.padding_hash(&hasher),
+            hasher,
+            options,
+            frontier: Frontier::default(),
+        }
+    }
+
+    /// Returns the number of leaves pushed so far.
+    pub fn len(&self) -> usize {
+        self.frontier.len
+    }
+
+    /// Returns true if no leaf was pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.frontier.len == 0
+    }
+
+    /// Appends a leaf holding `data`.
+    ///
+    /// # Returns
+    ///
+    /// The index of the new leaf.
+    pub fn push<T>(&mut self, data: T) -> usize
+    where
+        T: AsRef<[u8]>,
+    {
+        let hash = self
+            .options
+            .leaf_hash(&self.hasher, self.frontier.len, data.as_ref());
+        self.push_hash(hash)
+    }
+
+    /// Appends a leaf whose hash was computed elsewhere.
+    ///
+    /// # Returns
+    ///
+    /// The index of the new leaf.
+    pub fn push_hash(&mut self, hash: String) -> usize {
+        self.frontier.push(&self.hasher, hash);
+        self.frontier.len - 1
+    }
+
+    /// Returns the root over the leaves pushed so far, in O(log n).
+    ///
+    /// # Returns
+    ///
+    /// The root hash, or `None` if no leaf was pushed yet.
+    pub fn root(&self) -> Option<String> {
+        self.frontier
+            .root(&self.hasher, &self.options, self.padding_hash.as_deref())
+    }
+}
+
+#[cfg(all(test, feature = "sha256"))]
+mod tests {
+    use super::*;
+    use crate::{
+        hasher::SHA256Hasher,
+        merkletree::{MerkleTree, MerkleTreeBuilder, PaddingStrategy},
+    };
+
+    #[test]
+    fn test_root_after_each_push_matches_full_build() {
+        let hasher = SHA256Hasher::new();
+        let mut tree = IncrementalMerkleTree::new(hasher.clone());
+        assert!(tree.root().is_none());
+
+        let data: Vec<String> = (0..20).map(|i| format!("entry {i}")).collect();
+        for (index, item) in data.iter().enumerate() {
+            assert_eq!(tree.push(item), index);
+            let expected = MerkleTree::new(hasher.clone(), &data[..=index]);
+            assert_eq!(tree.root(), Some(expected.root_hex()));
+        }
+        assert_eq!(tree.len(), 20);
+    }
+
+    #[test]
+    fn test_root_respects_options() {
+        let hasher = SHA256Hasher::new();
+        let options = TreeOptions::new()
+            .bind_index(true)
+            .commit_length(true)
+            .padding(PaddingStrategy::HashEmpty);
+        let mut tree = IncrementalMerkleTree::with_options(hasher.clone(), options);
+
+        let data: Vec<String> = (0..13).map(|i| i.to_string()).collect();
+        for (index, item) in data.iter().enumerate() {
+            tree.push(item);
+            let expected =
+                MerkleTreeBuilder::with_options(hasher.clone(), options).build(&data[..=index]);
+            assert_eq!(tree.root(), Some(expected.root_hex()));
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Provides an indexed Merkle tree, an append-only set whose leaves form a sorted linked list,
+//! allowing both membership and non-membership proofs.
+//!
+//! Each leaf stores a value together with the next greater value in the set and the index of
+//! its leaf. A value is proven absent by its "low leaf": the leaf with the greatest value
+//! below it, whose next value is greater than it (or zero, meaning no greater value exists).
+
+use crate::{
+    error::MerkleError, hasher::Hasher, leaf::Leaf, merkletree::MerkleTree, proof::MerkleProof,
+};
+use std::{borrow::Cow, collections::BTreeMap};
+
+/// A leaf of an indexed Merkle tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexedLeaf {
+    /// The value stored in the leaf.
+    pub value: u64,
+    /// The next greater value of the set, or `0` if there is none.
+    pub next_value: u64,
+    /// The index of the leaf holding `next_value`, or `0` if there is none.
+    pub next_index: u64,
+}
+
+impl Leaf for IndexedLeaf {
+    fn encode(&self) -> Cow<'_, [u8]> {
+        let mut bytes = Vec::with_capacity(24);
+        bytes.extend_from_slice(&self.value.to_be_bytes());
+        bytes.extend_from_slice(&self.next_value.to_be_bytes());
+        bytes.extend_from_slice(&self.next_index.to_be_bytes());
+        Cow::Owned(bytes)
+    }
+}
+
+impl IndexedLeaf {
+    /// Returns `true` if `value` falls strictly between this leaf's value and the next one.
+    pub fn brackets(&self, value: u64) -> bool {
+        self.value < value && (self.next_value == 0 || value < self.next_value)
+    }
+}
+
+/// An append-only indexed Merkle tree of fixed capacity.
+///
+/// Leaf 0 always holds the zero value, which starts the linked list; unused slots hold the
+/// empty leaf `(0, 0, 0)`. Inserting a value recomputes only the paths of the new leaf and of
+/// its low leaf.
+pub struct IndexedMerkleTree<H: Hasher> {
+    hasher: H,
+    tree: MerkleTree,
+    leaves: Vec<IndexedLeaf>,
+    /// Index of the leaf holding each value, used to find low leaves.
+    indices: BTreeMap<u64, usize>,
+}
+
+impl<H> IndexedMerkleTree<H>
+where
+    H: Hasher + 'static + std::marker::Sync + Clone,
+{
+    /// Creates an indexed tree with room for `2^depth` leaves, including the zero leaf.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `depth` is 32 or more.
+    pub fn new(hasher: H, depth: u32) -> Self {
+        assert!(depth < 32, "Indexed Merkle Tree depth must be below 32");
+
+        let capacity = 1usize << depth;
+        let tree = MerkleTree::from_leaves(hasher.clone(), vec![IndexedLeaf::default(); capacity]);
+
+        Self {
+            hasher,
+            tree,
+            leaves: vec![IndexedLeaf::default()],
+            indices: BTreeMap::from([(0, 0)]),
+        }
+    }
+
+    /// Returns the underlying tree.
+    pub fn tree(&self) -> &MerkleTree {
+        &self.tree
+    }
+
+    /// Returns the root hash of the tree.
+    pub fn root_hex(&self) -> String {
+        self.tree.root_hex()
+    }
+
+    /// Returns the leaves inserted so far, starting with the zero leaf.
+    pub fn leaves(&self) -> &[IndexedLeaf] {
+        &self.leaves
+    }
+
+    /// Inserts `value` in the set.
+    ///
+    /// # Returns
+    ///
+    /// The index of the new leaf, `MerkleError::DuplicateValue` if `value` is already in the
+    /// set (zero always is), or `MerkleError::CapacityExceeded` if the tree is full.
+    pub fn insert(&mut self, value: u64) -> Result<usize, MerkleError> {
+        if self.indices.contains_key(&value) {
+            return Err(MerkleError::DuplicateValue { value });
+        }
+        let index = self.leaves.len();
+        if index >= self.tree.len() {
+            return Err(MerkleError::CapacityExceeded {
+                capacity: self.tree.len(),
+            });
+        }
+
+        let low_index = self.low_leaf_index(value);
+        let low = self.leaves[low_index];
+        self.leaves.push(IndexedLeaf {
+            value,
+            next_value: low.next_value,
+            next_index: low.next_index,
+        });
+        self.leaves[low_index].next_value = value;
+        self.leaves[low_index].next_index = index as u64;
+        self.indices.insert(value, index);
+
+        self.write_leaf(low_index)?;
+        self.write_leaf(index)?;
+
+        Ok(index)
+    }
+
+    /// Returns the index of the low leaf of `value`: the leaf with the greatest value below it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is zero, which has no low leaf.
+    pub fn low_leaf_index(&self, value: u64) -> usize {
+        let (_, &index) = self
+            .indices
+            .range(..value)
+            .next_back()
+            .expect("zero has no low leaf");
+        index
+    }
+
+    /// Proves that `value` is in the set.
+    ///
+    /// # Returns
+    ///
+    /// The leaf holding `value` and its proof, or `None` if `value` is not in the set.
+    pub fn prove_membership(&self, value: u64) -> Option<(IndexedLeaf, MerkleProof)> {
+        let &index = self.indices.get(&value)?;
+        self.prove_leaf(index)
+    }
+
+    /// Proves that `value` is not in the set.
+    ///
+    /// # Returns
+    ///
+    /// The low leaf of `value`, which brackets it, and its proof, or `None` if `value` is in
+    /// the set.
+    pub fn prove_non_membership(&self, value: u64) -> Option<(IndexedLeaf, MerkleProof)> {
+        if self.indices.contains_key(&value) {
+            return None;
+        }
+        self.prove_leaf(self.low_leaf_index(value))
+    }
+
+    fn prove_leaf(&self, index: usize) -> Option<(IndexedLeaf, MerkleProof)> {
+        Some((self.leaves[index], self.tree.generate_proof(index)?))
+    }
+
+    /// Rehashes the leaf at `index` into the underlying tree.
+    fn write_leaf(&mut self, index: usize) -> Result<(), MerkleError> {
+        let hash = self.hasher.hash(&self.leaves[index].encode());
+        self.tree.replace_leaf_hash(&self.hasher, index, hash)
+    }
+}
+
+/// Verifies that `leaf` is in the tree with root `root_hash` and holds `value`.
+pub fn verify_membership<H>(
+    hasher: &H,
+    leaf: &IndexedLeaf,
+    proof: &MerkleProof,
+    value: u64,
+    root_hash: &str,
+) -> bool
+where
+    H: Hasher,
+{
+    leaf.value == value && verify_leaf(hasher, leaf, proof, root_hash)
+}
+
+/// Verifies that `value` is absent from the tree with root `root_hash`, given its low leaf.
+///
+/// Unused slots hold the empty leaf `(0, 0, 0)`, which brackets every positive value, so a
+/// low leaf holding zero is only accepted at index 0, where the real zero leaf lives.
+pub fn verify_non_membership<H>(
+    hasher: &H,
+    low_leaf: &IndexedLeaf,
+    proof: &MerkleProof,
+    value: u64,
+    root_hash: &str,
+) -> bool
+where
+    H: Hasher,
+{
+    low_leaf.brackets(value)
+        && (low_leaf.value != 0 || proof.implied_index() == 0)
+        && verify_leaf(hasher, low_leaf, proof, root_hash)
+}
+
+/// Checks that `proof` carries the hash of `leaf` and reconstructs `root_hash`.
+fn verify_leaf<H>(hasher: &H, leaf: &IndexedLeaf, proof: &MerkleProof, root_hash: &str) -> bool
+where
+    H: Hasher,
+{
+    proof.leaf_hash.as_deref() == Some(hasher.hash(&leaf.encode()).as_str())
+        && proof.verify_standalone(hasher, root_hash)
+}
+
+#[cfg(all(test, feature = "sha256"))]
+mod tests {
+    use super::*;
+    use crate::hasher::SHA256Hasher;
+
+    #[test]
+    fn test_insert_links_leaves_in_order() {
+        let mut tree = IndexedMerkleTree::new(SHA256Hasher::new(), 3);
+        assert_eq!(tree.insert(30).unwrap(), 1);
+        assert_eq!(tree.insert(10).unwrap(), 2);
+        assert_eq!(tree.insert(20).unwrap(), 3);
+
+        let leaves = tree.leaves();
+        assert_eq!(
+            leaves[0],
+            IndexedLeaf {
+                value: 0,
+                next_value: 10,
+                next_index: 2
+            }
+        );
+        assert_eq!(
+            leaves[2],
+            IndexedLeaf {
+                value: 10,
+                next_value: 20,
+                next_index: 3
+            }
+        );
+        assert_eq!(
+            leaves[3],
+            IndexedLeaf {
+                value: 20,
+                next_value: 30,
+                next_index: 1
+            }
+        );
+        assert_eq!(
+            leaves[1],
+            IndexedLeaf {
+                value: 30,
+                next_value: 0,
+                next_index: 0
+            }
+        );
+
+        let mut expected: Vec<IndexedLeaf> = leaves.to_vec();
+        expected.resize(8, IndexedLeaf::default());
+        let rebuilt = MerkleTree::from_leaves(SHA256Hasher::new(), expected);
+        assert_eq!(tree.root_hex(), rebuilt.root_hex());
+    }
+
+    #[test]
+    fn test_insert_errors() {
+        let mut tree = IndexedMerkleTree::new(SHA256Hasher::new(), 1);
+        assert!(matches!(
+            tree.insert(0),
+            Err(MerkleError::DuplicateValue { value: 0 })
+        ));
+        tree.insert(5).unwrap();
+        assert!(matches!(
+            tree.insert(5),
+            Err(MerkleError::DuplicateValue { value: 5 })
+        ));
+        assert!(matches!(
+            tree.insert(6),
+            Err(MerkleError::CapacityExceeded { capacity: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_membership_and_non_membership_proofs() {
+        let hasher = SHA256Hasher::new();
+        let mut tree = IndexedMerkleTree::new(hasher.clone(), 4);
+        for value in [50, 10, 40, 70] {
+            tree.insert(value).unwrap();
+        }
+        let root = tree.root_hex();
+
+        let (leaf, proof) = tree.prove_membership(40).unwrap();
+        assert!(verify_membership(&hasher, &leaf, &proof, 40, &root));
+        assert!(!verify_membership(&hasher, &leaf, &proof, 41, &root));
+        assert!(tree.prove_membership(41).is_none());
+
+        let (low, proof) = tree.prove_non_membership(45).unwrap();
+        assert_eq!(low.value, 40);
+        assert!(verify_non_membership(&hasher, &low, &proof, 45, &root));
+        assert!(!verify_non_membership(&hasher, &low, &proof, 55, &root));
+
+        let (low, proof) = tree.prove_non_membership(100).unwrap();
+        assert_eq!(low.value, 70);
+        assert!(verify_non_membership(&hasher, &low, &proof, 100, &root));
+        assert!(tree.prove_non_membership(50).is_none());
+
+        let forged = IndexedLeaf {
+            next_value: 60,
+            ..low
+        };
+        assert!(!verify_non_membership(&hasher, &forged, &proof, 55, &root));
+    }
+
+    #[test]
+    fn test_empty_slot_is_not_a_low_leaf() {
+        let hasher = SHA256Hasher::new();
+        let mut tree = IndexedMerkleTree::new(hasher.clone(), 3);
+        tree.insert(50).unwrap();
+        let root = tree.root_hex();
+
+        let empty = IndexedLeaf::default();
+        let mut proof = tree.tree().generate_proof(5).unwrap();
+        assert!(proof.verify_standalone(&hasher, &root));
+        assert!(!verify_non_membership(&hasher, &empty, &proof, 50, &root));
+        proof.leaf_index = 0;
+        assert!(!verify_non_membership(&hasher, &empty, &proof, 50, &root));
+
+        // The real zero leaf still proves values below the smallest member absent.
+        let (low, proof) = tree.prove_non_membership(20).unwrap();
+        assert_eq!(low.value, 0);
+        assert!(verify_non_membership(&hasher, &low, &proof, 20, &root));
+    }
+}
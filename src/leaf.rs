@@ -0,0 +1,51 @@
+//! Defines the canonical encoding of the items committed to as leaves.
+
+use std::borrow::Cow;
+
+/// A value that can be committed to as a leaf of a Merkle tree.
+///
+/// Implement it for structured records to centralize their canonical byte encoding: the tree
+/// hashes whatever `encode` returns. Anything that is `AsRef<[u8]>` is a `Leaf` already.
+pub trait Leaf {
+    /// Returns the canonical bytes of the value.
+    fn encode(&self) -> Cow<'_, [u8]>;
+}
+
+impl<T> Leaf for T
+where
+    T: AsRef<[u8]> + ?Sized,
+{
+    fn encode(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Record {
+        id: u32,
+        name: &'static str,
+    }
+
+    impl Leaf for Record {
+        fn encode(&self) -> Cow<'_, [u8]> {
+            let mut bytes = self.id.to_be_bytes().to_vec();
+            bytes.extend_from_slice(self.name.as_bytes());
+            Cow::Owned(bytes)
+        }
+    }
+
+    #[test]
+    fn test_bytes_are_borrowed() {
+        assert!(matches!("abc".encode(), Cow::Borrowed(b"abc")));
+        assert_eq!(vec![1u8, 2].encode().as_ref(), &[1, 2]);
+    }
+
+    #[test]
+    fn test_custom_encoding() {
+        let record = Record { id: 1, name: "a" };
+        assert_eq!(record.encode().as_ref(), &[0, 0, 0, 1, b'a']);
+    }
+}
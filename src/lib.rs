@@ -4,6 +4,8 @@
 //! ## Example: Merkle Tree with File Inputs and Proof Verification
 //!
 //! ```rust
+//! # #[cfg(feature = "sha256")]
+//! # {
 //! use mt_rs::hasher::SHA256Hasher;
 //! use mt_rs::merkletree::MerkleTree;
 //! use mt_rs::proof::{DefaultProofer, Proofer};
@@ -49,10 +51,26 @@
 //!     &files[1],
 //!     "a08c44656fb3f561619b8747a0d1dabe97126d9ed6e0cafbd7ce08ebe12d55ca",
 //! ));
-//!
+//! # }
 //! ```
+pub mod encoding;
+pub mod error;
+#[cfg(feature = "keccak256")]
+pub mod ethereum;
+pub mod flat;
+pub mod forest;
 pub mod fs;
 pub mod hasher;
+pub mod hybrid;
+pub mod incremental;
+pub mod indexed;
+pub mod leaf;
+pub mod manifest;
 pub mod merkletree;
 pub mod node;
 pub mod proof;
+#[cfg(feature = "prost")]
+pub mod proto;
+pub mod sparse;
+#[cfg(feature = "trace")]
+pub mod trace;
@@ -0,0 +1,173 @@
+//! Provides a portable manifest of a tree: every leaf hash, the root and what is needed to
+//! rebuild it, in a canonical byte form to sign out of band.
+
+use crate::{
+    hasher::HashAlgorithm,
+    incremental::Frontier,
+    merkletree::{MerkleTree, PaddingStrategy, TreeOptions, aad_root},
+};
+
+/// Magic bytes opening the canonical encoding of a manifest, including its version.
+pub const MANIFEST_MAGIC: &[u8] = b"mt-rs:manifest:1\0";
+
+/// The leaf hashes and root of a tree, as returned by `MerkleTree::to_manifest`.
+///
+/// Anyone holding the manifest can check that the leaf hashes produce the root with `verify`,
+/// without the original data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    /// The algorithm the tree was built with.
+    pub algorithm: HashAlgorithm,
+    /// The hash of every leaf, in order.
+    pub leaf_hashes: Vec<String>,
+    /// The published root of the tree.
+    pub root: String,
+    /// Whether the root commits to the leaf count, see `TreeOptions::commit_length`.
+    pub commit_length: bool,
+    /// How odd levels were completed.
+    pub padding: PaddingStrategy,
+    /// The context bound to the root with `MerkleTree::with_aad`, if any.
+    pub aad: Option<Vec<u8>>,
+}
+
+impl Manifest {
+    /// Encodes the manifest deterministically, to be signed and checked out of band.
+    ///
+    /// The encoding is [`MANIFEST_MAGIC`], the algorithm name prefixed by its length as one
+    /// byte, a flags byte (bit 0 set if the length is committed, bit 1 for
+    /// `PaddingStrategy::HashEmpty`, bit 2 if context is bound), the leaf count as a
+    /// big-endian `u64`, the digest length as one byte, the raw leaf hashes, the raw root,
+    /// and finally the context prefixed by its length as a big-endian `u32`, if bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a hash is not hex-encoded or is longer than 255 bytes, which never happens
+    /// for manifests returned by `MerkleTree::to_manifest`.
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        let decode = |hash: &str| hex::decode(hash).expect("hash is not hex-encoded");
+        let name = self.algorithm.name().as_bytes();
+        let root = decode(&self.root);
+
+        let mut bytes = MANIFEST_MAGIC.to_vec();
+        bytes.push(name.len() as u8);
+        bytes.extend_from_slice(name);
+        bytes.push(
+            u8::from(self.commit_length)
+                | u8::from(self.padding == PaddingStrategy::HashEmpty) << 1
+                | u8::from(self.aad.is_some()) << 2,
+        );
+        bytes.extend_from_slice(&(self.leaf_hashes.len() as u64).to_be_bytes());
+        bytes.push(u8::try_from(root.len()).expect("hash is too long"));
+        for hash in &self.leaf_hashes {
+            bytes.extend(decode(hash));
+        }
+        bytes.extend(root);
+        if let Some(aad) = &self.aad {
+            bytes.extend_from_slice(&(aad.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(aad);
+        }
+
+        bytes
+    }
+
+    /// Rebuilds the root from the listed leaf hashes, in O(n) hashes and O(log n) memory.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the leaf hashes produce `root`, `false` otherwise or if there are none.
+    pub fn verify(&self) -> bool {
+        let options = TreeOptions::new()
+            .commit_length(self.commit_length)
+            .padding(self.padding);
+        let padding_hash = options.padding_hash(&self.algorithm);
+
+        let mut frontier = Frontier::default();
+        for hash in &self.leaf_hashes {
+            frontier.push(&self.algorithm, hash.clone());
+        }
+
+        let root = frontier.root(&self.algorithm, &options, padding_hash.as_deref());
+        let root = match (&self.aad, root) {
+            (Some(aad), Some(root)) => Some(aad_root(&self.algorithm, &root, aad)),
+            (None, root) => root,
+            (_, None) => None,
+        };
+        root.as_deref() == Some(self.root.as_str())
+    }
+}
+
+impl MerkleTree {
+    /// Exports the leaf hashes and root of the tree as a `Manifest`.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - The algorithm the tree was built with, recorded in the manifest.
+    pub fn to_manifest(&self, algorithm: HashAlgorithm) -> Manifest {
+        Manifest {
+            algorithm,
+            leaf_hashes: self
+                .leaves_ref()
+                .iter()
+                .map(|leaf| leaf.hash().to_string())
+                .collect(),
+            root: self.root_hex(),
+            commit_length: self.options().commits_length(),
+            padding: self.options().padding_strategy(),
+            aad: self.aad().map(<[u8]>::to_vec),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "sha256", feature = "blake3"))]
+mod tests {
+    use super::*;
+    use crate::merkletree::MerkleTreeBuilder;
+
+    #[test]
+    fn test_manifest_verifies_its_root() {
+        let algo = HashAlgorithm::Sha256;
+        let tree = MerkleTreeBuilder::new(algo)
+            .bind_index(true)
+            .commit_length(true)
+            .padding(PaddingStrategy::HashEmpty)
+            .build(["a", "b", "c", "d", "e"])
+            .with_aad(&algo, b"release 1.2");
+        let manifest = tree.to_manifest(algo);
+        assert_eq!(manifest.leaf_hashes.len(), 5);
+        assert!(manifest.verify());
+
+        let mut tampered = manifest.clone();
+        tampered.leaf_hashes.swap(0, 1);
+        assert!(!tampered.verify());
+
+        let mut tampered = manifest.clone();
+        tampered.aad = None;
+        assert!(!tampered.verify());
+
+        let mut tampered = manifest.clone();
+        tampered.algorithm = HashAlgorithm::Blake3;
+        assert!(!tampered.verify());
+
+        assert!(MerkleTree::new(algo, ["x"]).to_manifest(algo).verify());
+    }
+
+    #[test]
+    fn test_canonical_bytes() {
+        let algo = HashAlgorithm::Sha256;
+        let manifest = MerkleTree::new(algo, ["a", "b", "c"]).to_manifest(algo);
+        let bytes = manifest.to_canonical_bytes();
+
+        let header = MANIFEST_MAGIC.len() + 1 + "sha256".len() + 1 + 8 + 1;
+        assert!(bytes.starts_with(MANIFEST_MAGIC));
+        assert_eq!(bytes.len(), header + 4 * 32);
+        assert_eq!(bytes[header - 10], 0);
+        assert_eq!(&bytes[header - 9..header - 1], &3u64.to_be_bytes());
+        assert_eq!(bytes, manifest.clone().to_canonical_bytes());
+
+        let mut bound = manifest;
+        bound.aad = Some(b"ctx".to_vec());
+        let bound_bytes = bound.to_canonical_bytes();
+        assert_eq!(bound_bytes[header - 10], 0b100);
+        assert!(bound_bytes.ends_with(b"\0\0\0\x03ctx"));
+    }
+}
@@ -1,8 +1,437 @@
 //! Provides the MerkleTree structure and associated methods for creating and interacting
 //! with binary Merkle trees using custom hashers.
 
-use crate::{fs, hasher::Hasher, node::Node};
+#[cfg(feature = "trace")]
+use crate::trace::BuildTrace;
+use crate::{
+    error::MerkleError,
+    fs::{self, HashDirOptions},
+    hasher::{HashAlgorithm, Hasher},
+    leaf::Leaf,
+    node::{Node, NodeChildType},
+    proof::{DefaultProofer, MerkleProof, ProofNode},
+};
 use rayon::prelude::*;
+use std::path::PathBuf;
+
+/// Domain prefix of the tombstones that replace deleted leaves.
+pub const TOMBSTONE_DOMAIN: &[u8] = b"mt-rs:tombstone\0";
+
+/// Domain prefix of the leaves of revocable trees, so that no data hashes to a tombstone.
+pub const LEAF_DOMAIN: &[u8] = b"mt-rs:leaf\0";
+
+/// Default number of nodes in a level below which it is combined sequentially instead of
+/// with Rayon.
+pub const DEFAULT_PARALLEL_THRESHOLD: usize = 64;
+
+/// How a level with an odd number of nodes is completed before pairing its nodes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PaddingStrategy {
+    /// The last node is paired with a copy of itself.
+    #[default]
+    Duplicate,
+    /// The last node is paired with the hash of the empty byte string, a constant that does
+    /// not depend on the content of the tree.
+    ///
+    /// This pad collides with the hash of a leaf holding empty data: a dataset and the same
+    /// dataset followed by an empty item can share a root. Only
+    /// `TreeOptions::commit_length` removes that ambiguity.
+    HashEmpty,
+}
+
+/// How leaves are salted before hashing, to hide low-entropy leaf data from anyone who only
+/// sees the hashes. The salt is hashed before the data, and before the index if bound.
+///
+/// A salt derived from the content is set with `MerkleTreeBuilder::derive_leaf_salt`.
+///
+/// Variants depend on the enabled features, so matches need a wildcard arm.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LeafSaltMode {
+    /// Leaves are not salted.
+    #[default]
+    None,
+    /// Every leaf is salted with the given salt.
+    Fixed([u8; 32]),
+    /// Every leaf is salted with a random salt, drawn from the thread-local CSPRNG as soon as
+    /// the option is set: the options then hold it as `Fixed`, see `TreeOptions::leaf_salt`.
+    #[cfg(feature = "rand")]
+    Random,
+}
+
+/// Options controlling how the levels of a tree are built, shared by `MerkleTreeBuilder` and
+/// the proofers.
+#[derive(Debug, Clone, Copy)]
+pub struct TreeOptions {
+    parallel_threshold: usize,
+    bind_index: bool,
+    commit_length: bool,
+    padding: PaddingStrategy,
+    leaf_salt: LeafSaltMode,
+    revocable: bool,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        Self {
+            parallel_threshold: DEFAULT_PARALLEL_THRESHOLD,
+            bind_index: false,
+            commit_length: false,
+            padding: PaddingStrategy::Duplicate,
+            leaf_salt: LeafSaltMode::None,
+            revocable: false,
+        }
+    }
+}
+
+impl TreeOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of nodes in a level from which pairs are combined in parallel.
+    ///
+    /// Smaller levels are combined with a plain loop, avoiding the Rayon dispatch overhead
+    /// that dominates for tiny trees. `0` always parallelizes, `usize::MAX` never does.
+    pub fn parallel_threshold(mut self, threshold: usize) -> Self {
+        self.parallel_threshold = threshold;
+        self
+    }
+
+    /// Binds each leaf to its position by hashing `index_le_u64 || data` instead of `data`.
+    ///
+    /// A proof then only verifies for the data at the index it was generated for, even if the
+    /// siblings of another position would line up.
+    pub fn bind_index(mut self, bind: bool) -> Self {
+        self.bind_index = bind;
+        self
+    }
+
+    /// Makes the root commit to the number of leaves: `root' = H(root || leaf_count_le_u64)`.
+    ///
+    /// Without it, padding lets a dataset share its root with a longer one: the same dataset
+    /// with its last element repeated, or followed by an empty item with
+    /// `PaddingStrategy::HashEmpty`.
+    pub fn commit_length(mut self, commit: bool) -> Self {
+        self.commit_length = commit;
+        self
+    }
+
+    /// Sets how odd levels are completed before pairing. See `PaddingStrategy`.
+    pub fn padding(mut self, strategy: PaddingStrategy) -> Self {
+        self.padding = strategy;
+        self
+    }
+
+    /// Allows leaves to be deleted with `MerkleTree::delete_leaf`, leaving a tombstone.
+    ///
+    /// Leaves are then hashed as `LEAF_DOMAIN || salt || index || data` and tombstones as
+    /// `TOMBSTONE_DOMAIN || salt || index`, where salt and index are only present if enabled.
+    /// The two domains are disjoint, so no data hashes to a tombstone and a tombstone proves
+    /// a deliberate deletion.
+    pub fn revocable(mut self, revocable: bool) -> Self {
+        self.revocable = revocable;
+        self
+    }
+
+    /// Sets how leaves are salted. See `LeafSaltMode`.
+    pub fn leaf_salt_mode(mut self, mode: LeafSaltMode) -> Self {
+        self.leaf_salt = match mode {
+            #[cfg(feature = "rand")]
+            LeafSaltMode::Random => LeafSaltMode::Fixed(rand::random()),
+            mode => mode,
+        };
+        self
+    }
+
+    /// Returns the salt of the leaves, if they are salted and the salt is already known.
+    pub fn leaf_salt(&self) -> Option<[u8; 32]> {
+        match self.leaf_salt {
+            LeafSaltMode::Fixed(salt) => Some(salt),
+            _ => None,
+        }
+    }
+
+    /// Returns the published root of a tree of `leaf_count` leaves whose top node is `root`:
+    /// `root` itself, or its mix with the leaf count if the length is committed.
+    pub fn root_hash<H>(&self, hasher: &H, root: &str, leaf_count: usize) -> String
+    where
+        H: Hasher,
+    {
+        if self.commit_length {
+            let count = (leaf_count as u64).to_le_bytes();
+            hasher.hash(&[root.as_bytes(), &count].concat())
+        } else {
+            root.to_string()
+        }
+    }
+
+    /// Returns the hash of the leaf holding `data` at position `index`.
+    pub fn leaf_hash<H>(&self, hasher: &H, index: usize, data: &[u8]) -> String
+    where
+        H: Hasher,
+    {
+        let mut input = self.leaf_prefix(index);
+        if input.is_empty() {
+            return hasher.hash(data);
+        }
+        input.extend_from_slice(data);
+        hasher.hash(&input)
+    }
+
+    /// Returns the tombstone of the leaf at position `index`, or `None` if leaves cannot be
+    /// deleted. See `revocable`.
+    pub fn tombstone_hash<H>(&self, hasher: &H, index: usize) -> Option<String>
+    where
+        H: Hasher,
+    {
+        self.revocable
+            .then(|| hasher.hash(&self.domain_prefix(TOMBSTONE_DOMAIN, index)))
+    }
+
+    /// Returns the constant hash odd levels are padded with, or `None` if the last node is
+    /// duplicated instead. Computed once per tree or proofer.
+    pub(crate) fn padding_hash<H>(&self, hasher: &H) -> Option<String>
+    where
+        H: Hasher,
+    {
+        match self.padding {
+            PaddingStrategy::Duplicate => None,
+            PaddingStrategy::HashEmpty => Some(hasher.empty_hash()),
+        }
+    }
+
+    /// Returns `true` if the root commits to the leaf count.
+    pub(crate) fn commits_length(&self) -> bool {
+        self.commit_length
+    }
+
+    /// Returns how odd levels are completed.
+    pub(crate) fn padding_strategy(&self) -> PaddingStrategy {
+        self.padding
+    }
+
+    /// Returns `true` if leaves are bound to their position.
+    pub(crate) fn binds_index(&self) -> bool {
+        self.bind_index
+    }
+
+    /// Bytes hashed before the data of the leaf at `index`: the leaf domain if the tree is
+    /// revocable, the salt, then the index.
+    pub(crate) fn leaf_prefix(&self, index: usize) -> Vec<u8> {
+        let domain = if self.revocable { LEAF_DOMAIN } else { &[] };
+        self.domain_prefix(domain, index)
+    }
+
+    /// Returns `domain`, followed by the salt and the index of the leaf at `index`.
+    fn domain_prefix(&self, domain: &[u8], index: usize) -> Vec<u8> {
+        let mut prefix = domain.to_vec();
+        if let LeafSaltMode::Fixed(salt) = self.leaf_salt {
+            prefix.extend_from_slice(&salt);
+        }
+        if self.bind_index {
+            prefix.extend_from_slice(&(index as u64).to_le_bytes());
+        }
+        prefix
+    }
+
+    /// Returns `true` if the options leave the root ambiguous about the leaf count, i.e. the
+    /// count is not committed. Whatever the padding, a dataset can then share its root with a
+    /// longer one: with its last leaf repeated when duplicating, or followed by an empty item
+    /// with `PaddingStrategy::HashEmpty`.
+    #[cfg(feature = "log")]
+    pub(crate) fn is_size_ambiguous(&self) -> bool {
+        !self.commit_length
+    }
+
+    /// Warns once per process, through `log`, if the options are weak. See
+    /// `is_size_ambiguous`.
+    #[cfg(feature = "log")]
+    pub(crate) fn warn_if_weak(&self) {
+        static WARNED: std::sync::Once = std::sync::Once::new();
+
+        if self.is_size_ambiguous() {
+            WARNED.call_once(|| {
+                log::warn!(
+                    "mt-rs: building a tree without committing to the leaf count, so a dataset \
+                     can share its root with the same dataset padded by one leaf (its last leaf \
+                     repeated, or an empty item with PaddingStrategy::HashEmpty). Consider \
+                     TreeOptions::commit_length. If your hasher does not already separate leaves \
+                     from internal nodes, consider a DualHasher too."
+                );
+            });
+        }
+    }
+
+    /// Applies `f` to each pair of `level`, in parallel if the level is large enough.
+    pub(crate) fn combine_pairs<T, R, F>(&self, level: &[T], f: F) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T, &T) -> R + Sync + Send,
+    {
+        if level.len() < self.parallel_threshold {
+            level.chunks(2).map(|pair| f(&pair[0], &pair[1])).collect()
+        } else {
+            level
+                .par_chunks(2)
+                .map(|pair| f(&pair[0], &pair[1]))
+                .collect()
+        }
+    }
+}
+
+/// Builds a `MerkleTree` with non-default `TreeOptions`.
+pub struct MerkleTreeBuilder<H: Hasher> {
+    hasher: H,
+    options: TreeOptions,
+    /// Whether the leaf salt is derived from the content when building.
+    derive_salt: bool,
+}
+
+impl<H> MerkleTreeBuilder<H>
+where
+    H: Hasher + 'static + std::marker::Sync,
+{
+    /// Creates a builder using `hasher` and the default options.
+    pub fn new(hasher: H) -> Self {
+        Self::with_options(hasher, TreeOptions::default())
+    }
+
+    /// Creates a builder using `hasher` and previously configured `options`.
+    pub fn with_options(hasher: H, options: TreeOptions) -> Self {
+        Self {
+            hasher,
+            options,
+            derive_salt: false,
+        }
+    }
+
+    /// Sets the number of nodes in a level from which pairs are combined in parallel.
+    ///
+    /// See `TreeOptions::parallel_threshold`.
+    pub fn parallel_threshold(mut self, threshold: usize) -> Self {
+        self.options = self.options.parallel_threshold(threshold);
+        self
+    }
+
+    /// Binds each leaf to its position. See `TreeOptions::bind_index`.
+    pub fn bind_index(mut self, bind: bool) -> Self {
+        self.options = self.options.bind_index(bind);
+        self
+    }
+
+    /// Makes the root commit to the number of leaves. See `TreeOptions::commit_length`.
+    pub fn commit_length(mut self, commit: bool) -> Self {
+        self.options = self.options.commit_length(commit);
+        self
+    }
+
+    /// Sets how odd levels are completed before pairing. See `PaddingStrategy`.
+    pub fn padding(mut self, strategy: PaddingStrategy) -> Self {
+        self.options = self.options.padding(strategy);
+        self
+    }
+
+    /// Allows leaves to be deleted. See `TreeOptions::revocable`.
+    pub fn revocable(mut self, revocable: bool) -> Self {
+        self.options = self.options.revocable(revocable);
+        self
+    }
+
+    /// Sets how leaves are salted, replacing a salt derived from the content. See
+    /// `LeafSaltMode`.
+    pub fn leaf_salt_mode(mut self, mode: LeafSaltMode) -> Self {
+        self.derive_salt = false;
+        self.options = self.options.leaf_salt_mode(mode);
+        self
+    }
+
+    /// Salts every leaf with the hash of all the unsalted leaf hashes, so the same data always
+    /// yields the same salted tree without any RNG.
+    ///
+    /// The salt is derived by `build`, which needs two passes: one to hash the leaves unsalted
+    /// and one to hash them salted, holding the whole input in between. The options of the
+    /// built tree hold the salt as `LeafSaltMode::Fixed`, and are the ones to give to
+    /// proofers. Anyone knowing the whole dataset can derive the salt too.
+    pub fn derive_leaf_salt(mut self) -> Self {
+        self.derive_salt = true;
+        self
+    }
+
+    /// Returns the options configured so far, to build a matching proofer.
+    ///
+    /// A salt derived from the content is only known once built, so these options do not hold
+    /// it yet: use the options of the built tree instead.
+    pub fn options(&self) -> &TreeOptions {
+        &self.options
+    }
+
+    /// Builds the tree over `data`, as `MerkleTree::new` does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    pub fn build<I, T>(mut self, data: I) -> MerkleTree
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        if self.derive_salt {
+            let data: Vec<T> = data.into_iter().collect();
+            let unsalted = self.options.leaf_salt_mode(LeafSaltMode::None);
+            let leaf_hashes: String = data
+                .iter()
+                .enumerate()
+                .map(|(index, item)| unsalted.leaf_hash(&self.hasher, index, item.as_ref()))
+                .collect();
+            let salt = derive_salt(&self.hasher.hash(leaf_hashes.as_bytes()));
+            self.options = self.options.leaf_salt_mode(LeafSaltMode::Fixed(salt));
+            self.derive_salt = false;
+            return self.build(data);
+        }
+
+        let leaves: Vec<Node> = data
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| {
+                Node::new_leaf(self.options.leaf_hash(&self.hasher, index, item.as_ref()))
+            })
+            .collect();
+
+        assert!(
+            !leaves.is_empty(),
+            "Merkle Tree requires at least one element"
+        );
+
+        MerkleTree::try_build(
+            self.hasher,
+            leaves,
+            &self.options,
+            &HashDirOptions::default(),
+        )
+        .expect("build without cancellation flag cannot fail")
+    }
+}
+
+/// Mixes external context into a root, as bound by `MerkleTree::with_aad`.
+pub(crate) fn aad_root<H>(hasher: &H, root: &str, aad: &[u8]) -> String
+where
+    H: Hasher,
+{
+    hasher.hash(&[root.as_bytes(), aad].concat())
+}
+
+/// Turns a hash into a 32-byte salt: its first 32 bytes, zero-padded if shorter. Hashes that
+/// are not hex are taken as raw bytes.
+fn derive_salt(hash: &str) -> [u8; 32] {
+    let bytes = hex::decode(hash).unwrap_or_else(|_| hash.as_bytes().to_vec());
+    let mut salt = [0; 32];
+    let len = bytes.len().min(salt.len());
+    salt[..len].copy_from_slice(&bytes[..len]);
+    salt
+}
 
 /// A binary Merkle tree implementation.
 ///
@@ -10,12 +439,42 @@ use rayon::prelude::*;
 /// Each leaf node contains the hash of a data item, and each internal node contains the hash
 /// of the concatenation of its children's hashes.
 pub struct MerkleTree {
-    /// Leaf nodes at the base of the tree (may include a duplicate for even pairing).
+    /// Leaf nodes at the base of the tree, without the duplicate used for even pairing.
     leaves: Vec<Node>,
     /// Height of the tree (number of levels including root).
     height: usize,
     /// Root node of the Merkle tree.
     root: Node,
+    /// Published root hash: the hash of `root`, mixed with the leaf count if it is committed.
+    root_hash: String,
+    /// Hashes of the internal levels, from the parents of the leaves up to the root, without
+    /// padding duplicates.
+    levels: Vec<Vec<String>>,
+    /// Path of the file behind each leaf, if the tree was built from paths.
+    paths: Vec<PathBuf>,
+    /// Options the tree was built with.
+    options: TreeOptions,
+    /// Constant hash odd levels are padded with, if not duplicating their last node.
+    padding_hash: Option<String>,
+    /// Context mixed into the published root, if bound with `with_aad`.
+    aad: Option<Vec<u8>>,
+    /// Every combine made by the build.
+    #[cfg(feature = "trace")]
+    trace: BuildTrace,
+}
+
+/// A leaf of a tree together with everything a client needs to authenticate it, as returned
+/// by `MerkleTree::entry`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The index of the leaf.
+    pub leaf_index: usize,
+    /// The hash of the leaf.
+    pub leaf_hash: String,
+    /// The proof of the leaf.
+    pub proof: MerkleProof,
+    /// The published root the proof leads to.
+    pub root: String,
 }
 
 impl MerkleTree {
@@ -24,7 +483,8 @@ impl MerkleTree {
     /// # Arguments
     ///
     /// * `hasher` - A reference to an implementation of the `Hasher` trait.
-    /// * `data` - A vector of values to be converted into leaf nodes.
+    /// * `data` - An iterator of values to be converted into leaf nodes. Items are hashed as they
+    ///   are consumed and dropped right after, so the whole input is never held at once.
     ///
     /// # Panics
     ///
@@ -40,145 +500,2306 @@ impl MerkleTree {
         T: AsRef<[u8]>,
         H: Hasher + 'static + std::marker::Sync,
     {
-        let owned_data: Vec<T> = data.into_iter().collect();
-        let data_slices: Vec<&[u8]> = owned_data.iter().map(|item| item.as_ref()).collect();
+        // Each item is hashed as soon as it is consumed, so only the leaf hashes are kept alive.
+        let leaves: Vec<Node> = data
+            .into_iter()
+            .map(|item| Node::new_leaf(hasher.hash(item.as_ref())))
+            .collect();
 
         assert!(
-            !data_slices.is_empty(),
+            !leaves.is_empty(),
             "Merkle Tree requires at least one element"
         );
 
-        let leaves: Vec<Node> = data_slices
-            .iter()
-            .map(|data| Node::new_leaf(hasher.hash(data)))
-            .collect();
-
         Self::build(hasher, leaves)
     }
 
-    /// Construct a Merkletree from an iter of String-s.
-    pub fn from_paths<H>(hasher: H, paths: Vec<String>) -> Self
+    /// Creates a new `MerkleTree` from borrowed byte slices, hashing each in place.
+    ///
+    /// Only the leaf hashes are stored, so nothing is copied out of `data`. `new` accepts
+    /// borrowed items as well; this is a non-generic shorthand for data already held as slices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    pub fn from_slices<H>(hasher: H, data: &[&[u8]]) -> Self
     where
-        H: Hasher + 'static + std::marker::Sync + Clone,
+        H: Hasher + 'static + std::marker::Sync,
     {
-        let leaves = fs::hash_dir(hasher.clone(), paths);
-
-        Self::build(hasher, leaves)
+        Self::new(hasher, data)
     }
 
-    /// Constructs the internal nodes of the tree from the leaves upward and computes the root.
-    fn build<H>(hasher: H, mut leaves: Vec<Node>) -> Self
+    /// Creates a new `MerkleTree` from a buffer of fixed-size records, each record being a
+    /// leaf hashed in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - A reference to an implementation of the `Hasher` trait.
+    /// * `buffer` - The records, one after the other.
+    /// * `record_size` - The size of each record, in bytes.
+    ///
+    /// # Returns
+    ///
+    /// The tree, or `MerkleError::InvalidRecordSize` if `buffer` is empty, `record_size` is
+    /// zero, or the length of `buffer` is not a multiple of `record_size`.
+    pub fn from_record_buffer<H>(
+        hasher: H,
+        buffer: &[u8],
+        record_size: usize,
+    ) -> Result<Self, MerkleError>
     where
         H: Hasher + 'static + std::marker::Sync,
     {
-        let original_leaves = leaves.clone();
-        let mut height = 1;
-
-        while leaves.len() > 1 {
-            if leaves.len() % 2 != 0 {
-                leaves.push(leaves.last().unwrap().clone());
-            }
-
-            leaves = leaves
-                .par_chunks(2)
-                .map(|pair| {
-                    let combined = [pair[0].hash().as_bytes(), pair[1].hash().as_bytes()].concat();
-                    let hash = hasher.hash(&combined);
-                    Node::new_internal(hash, pair[0].clone(), pair[1].clone())
-                })
-                .collect();
-
-            height += 1;
+        if buffer.is_empty() || record_size == 0 || !buffer.len().is_multiple_of(record_size) {
+            return Err(MerkleError::InvalidRecordSize {
+                len: buffer.len(),
+                record_size,
+            });
         }
 
-        MerkleTree {
-            leaves: original_leaves,
-            height,
-            root: leaves.into_iter().next().expect("root not found"),
-        }
+        Ok(Self::new(hasher, buffer.chunks_exact(record_size)))
     }
 
-    /// Returns the height (number of levels) of the tree.
-    pub fn height(&self) -> usize {
-        self.height
-    }
+    /// Creates a new `MerkleTree` from typed items, hashing the canonical encoding of each.
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - A reference to an implementation of the `Hasher` trait.
+    /// * `items` - An iterator of values implementing `Leaf`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `items` is empty.
+    pub fn from_leaves<I, L, H>(hasher: H, items: I) -> Self
+    where
+        I: IntoIterator<Item = L>,
+        L: Leaf,
+        H: Hasher + 'static + std::marker::Sync,
+    {
+        let leaves: Vec<Node> = items
+            .into_iter()
+            .map(|item| Node::new_leaf(hasher.hash(&item.encode())))
+            .collect();
 
-    /// Returns true if the tree has no leaves (should never happen if `new()` was used).
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
-    }
+        assert!(
+            !leaves.is_empty(),
+            "Merkle Tree requires at least one element"
+        );
 
-    /// Returns the number of leaf nodes in the tree.
-    pub fn len(&self) -> usize {
-        self.leaves.len()
+        Self::build(hasher, leaves)
     }
 
-    /// Returns the tree' leaves.
-    pub fn leaves(&self) -> Vec<Node> {
-        self.leaves.clone()
-    }
+    /// Creates a new `MerkleTree` with one leaf per line read from `reader`.
+    ///
+    /// Lines are taken as raw bytes without their trailing `\n` or `\r\n`; the last line
+    /// does not need one.
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - A reference to an implementation of the `Hasher` trait.
+    /// * `reader` - The newline-delimited input, e.g. stdin.
+    /// * `skip_blank` - Whether to skip empty lines instead of hashing them as leaves.
+    ///
+    /// # Returns
+    ///
+    /// The tree, or the error returned by `reader`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no line is left to hash.
+    pub fn from_lines<R, H>(hasher: H, mut reader: R, skip_blank: bool) -> std::io::Result<Self>
+    where
+        R: std::io::BufRead,
+        H: Hasher + 'static + std::marker::Sync,
+    {
+        let mut leaves: Vec<Node> = Vec::new();
+        let mut line = Vec::new();
 
-    /// Returns the root node of the tree.
-    pub fn root(&self) -> Node {
-        self.root.clone()
-    }
-}
+        while reader.read_until(b'\n', &mut line)? > 0 {
+            if line.last() == Some(&b'\n') {
+                line.pop();
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+            }
+            if !(skip_blank && line.is_empty()) {
+                leaves.push(Node::new_leaf(hasher.hash(&line)));
+            }
+            line.clear();
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::hasher::*;
+        assert!(
+            !leaves.is_empty(),
+            "Merkle Tree requires at least one element"
+        );
 
-    #[test]
-    fn test_merkle_tree_with_default_hasher() {
-        let data = &["hello".as_bytes(), "world".as_bytes()];
-        let tree = MerkleTree::new(DummyHasher, data);
+        Ok(Self::build(hasher, leaves))
+    }
 
-        assert_eq!(tree.height(), 2);
-        assert_eq!(tree.root().hash(), "hash_539");
+    /// Creates a new `MerkleTree` from the items sent on a channel, e.g. by a producer thread.
+    ///
+    /// Each item is hashed as it arrives and dropped right after, so only the leaf hashes are
+    /// kept while the producer runs. The tree is built once every sender is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - A reference to an implementation of the `Hasher` trait.
+    /// * `rx` - The receiving end of the channel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel closes without any item.
+    pub fn from_receiver<H>(hasher: H, rx: std::sync::mpsc::Receiver<Vec<u8>>) -> Self
+    where
+        H: Hasher + 'static + std::marker::Sync,
+    {
+        Self::new(hasher, rx)
     }
 
-    #[test]
-    fn test_merkle_tree_hashing() {
-        let data = &["hello".as_bytes(), "world".as_bytes()];
-        let tree = MerkleTree::new(SHA256Hasher::new(), data);
+    /// Creates a new `MerkleTree` from leaf hashes persisted earlier, without the original
+    /// data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hashes` is empty.
+    pub fn from_leaf_hashes<I, S, H>(hasher: H, hashes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+        H: Hasher + 'static + std::marker::Sync,
+    {
+        let leaves: Vec<Node> = hashes
+            .into_iter()
+            .map(|hash| Node::new_leaf(hash.into()))
+            .collect();
 
-        assert_eq!(tree.height(), 2);
-        assert_eq!(
-            tree.root().hash(),
-            "15e178b71fae8849ee562c9cc0d7ea322fba6cd495411329d47234479167cc8b"
+        assert!(
+            !leaves.is_empty(),
+            "Merkle Tree requires at least one element"
         );
-    }
 
+        Self::build(hasher, leaves)
+    }
+
+    /// Creates a new `MerkleTree` from a fixed-size array of data items.
+    ///
+    /// Exactly `N` leaves are allocated up front.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is zero.
+    pub fn from_array<const N: usize, T, H>(hasher: H, data: [T; N]) -> Self
+    where
+        T: AsRef<[u8]>,
+        H: Hasher + 'static + std::marker::Sync,
+    {
+        assert!(N > 0, "Merkle Tree requires at least one element");
+
+        let mut leaves: Vec<Node> = Vec::with_capacity(N);
+        for item in &data {
+            leaves.push(Node::new_leaf(hasher.hash(item.as_ref())));
+        }
+
+        Self::build(hasher, leaves)
+    }
+
+    /// Creates a new `MerkleTree` from asynchronous readers, one leaf per reader.
+    ///
+    /// Each reader is hashed in chunks, so at most one chunk per file is held in memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `readers` is empty.
+    #[cfg(feature = "tokio")]
+    pub async fn from_async_readers<I, R, H>(hasher: H, readers: I) -> std::io::Result<Self>
+    where
+        I: IntoIterator<Item = R>,
+        R: tokio::io::AsyncRead + Unpin,
+        H: Hasher + 'static + std::marker::Sync,
+    {
+        let mut leaves: Vec<Node> = Vec::new();
+        for reader in readers {
+            let hash = crate::hasher::hash_async_read(&hasher, reader).await?;
+            leaves.push(Node::new_leaf(hash));
+        }
+
+        assert!(
+            !leaves.is_empty(),
+            "Merkle Tree requires at least one element"
+        );
+
+        Ok(Self::build(hasher, leaves))
+    }
+
+    /// Creates a new `MerkleTree` by building `shards` subtrees in parallel and stitching them.
+    ///
+    /// The data is split into contiguous shards whose size is rounded up to a power of two, so
+    /// every shard root sits at the same level of the final tree; fewer shards than requested
+    /// may therefore be used. The last shard may be shorter: its root is lifted by duplication
+    /// to the common height, exactly as padding would do in a direct build.
+    ///
+    /// # Returns
+    ///
+    /// The final tree, identical to `MerkleTree::new` over the same data, and the root hash of
+    /// each shard subtree, in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty or `shards` is zero.
+    pub fn build_sharded<T, H>(hasher: H, data: &[T], shards: usize) -> (Self, Vec<String>)
+    where
+        T: AsRef<[u8]> + std::marker::Sync,
+        H: Hasher + 'static + std::marker::Sync + Clone,
+    {
+        assert!(
+            !data.is_empty(),
+            "Merkle Tree requires at least one element"
+        );
+        assert!(shards > 0, "Merkle Tree requires at least one shard");
+
+        let shard_size = data.len().div_ceil(shards).next_power_of_two();
+        let shard_height = shard_size.trailing_zeros() as usize + 1;
+
+        let subtrees: Vec<MerkleTree> = data
+            .par_chunks(shard_size)
+            .map(|chunk| {
+                let leaves = chunk
+                    .iter()
+                    .map(|item| Node::new_leaf(hasher.hash(item.as_ref())))
+                    .collect();
+                let mut subtree = Self::build(hasher.clone(), leaves);
+
+                while subtree.height < shard_height {
+                    let root = subtree.root.clone();
+                    let hash = hasher.combine(root.hash(), root.hash());
+                    #[cfg(feature = "trace")]
+                    subtree
+                        .trace
+                        .record(subtree.height, root.hash(), root.hash(), &hash);
+                    subtree.levels.push(vec![hash.clone()]);
+                    subtree.root = Node::new_internal(hash, root.clone(), root);
+                    subtree.height += 1;
+                }
+
+                subtree
+            })
+            .collect();
+
+        let shard_roots: Vec<String> = subtrees
+            .iter()
+            .map(|subtree| subtree.root.hash().to_string())
+            .collect();
+        // Shards are contiguous, so each level of the final tree is the concatenation of the
+        // same level of every shard, followed by the levels above the shard roots.
+        let mut levels: Vec<Vec<String>> = vec![Vec::new(); shard_height - 1];
+        let mut leaves = Vec::with_capacity(data.len());
+        let mut roots = Vec::with_capacity(subtrees.len());
+        #[cfg(feature = "trace")]
+        let mut trace = BuildTrace::default();
+        for subtree in subtrees {
+            #[cfg(feature = "trace")]
+            trace.append(subtree.trace, 0);
+            leaves.extend(subtree.leaves);
+            for (level, hashes) in levels.iter_mut().zip(subtree.levels) {
+                level.extend(hashes);
+            }
+            roots.push(subtree.root);
+        }
+
+        let top = Self::build(hasher, roots);
+        #[cfg(feature = "trace")]
+        {
+            trace.append(top.trace, shard_height - 1);
+            trace.sort_by_level();
+        }
+        levels.extend(top.levels);
+        let tree = MerkleTree {
+            leaves,
+            height: shard_height + top.height - 1,
+            root_hash: top.root_hash,
+            root: top.root,
+            levels,
+            paths: vec![],
+            options: TreeOptions::default(),
+            padding_hash: None,
+            aad: None,
+            #[cfg(feature = "trace")]
+            trace,
+        };
+
+        (tree, shard_roots)
+    }
+
+    /// Creates the row and column commitments of a matrix of cells.
+    ///
+    /// Each row is hashed into a row root, and each column into a column root, using a regular
+    /// tree over its cells. The two returned trees have those roots as leaves, so a cell can be
+    /// proven through its row (cell in row, row root in row tree) and through its column.
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - A reference to an implementation of the `Hasher` trait.
+    /// * `rows` - The matrix, as a list of rows of cells.
+    ///
+    /// # Returns
+    ///
+    /// The tree over the row roots and the tree over the column roots.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix has no cells or if its rows have different lengths.
+    pub fn from_matrix<H>(hasher: H, rows: &[Vec<Vec<u8>>]) -> (Self, Self)
+    where
+        H: Hasher + 'static + std::marker::Sync + Clone,
+    {
+        let width = rows.first().map_or(0, Vec::len);
+        assert!(width > 0, "Merkle Tree requires at least one element");
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "All rows of the matrix must have the same length"
+        );
+
+        let row_roots: Vec<Node> = rows
+            .iter()
+            .map(|row| Node::new_leaf(Self::new(hasher.clone(), row).root_hex()))
+            .collect();
+        let column_roots: Vec<Node> = (0..width)
+            .map(|column| {
+                let cells = rows.iter().map(|row| &row[column]);
+                Node::new_leaf(Self::new(hasher.clone(), cells).root_hex())
+            })
+            .collect();
+
+        (
+            Self::build(hasher.clone(), row_roots),
+            Self::build(hasher, column_roots),
+        )
+    }
+
+    /// Construct a Merkletree from an iter of paths, such as `String`s, `&str`s or `PathBuf`s.
+    pub fn from_paths<H, I, P>(hasher: H, paths: I) -> Self
+    where
+        H: Hasher + 'static + std::marker::Sync + Clone,
+        I: IntoIterator<Item = P>,
+        P: AsRef<std::path::Path>,
+    {
+        fs::exit_on_error(Self::from_paths_with_options(
+            hasher,
+            paths,
+            &HashDirOptions::default(),
+        ))
+    }
+
+    /// Construct a Merkletree from an iter of paths, returning an error instead of exiting when
+    /// a file cannot be read.
+    ///
+    /// Every path is checked before hashing anything: if some are missing or unreadable,
+    /// `MerkleError::UnreadablePaths` lists all of them.
+    ///
+    /// The cancellation flag of `options`, if any, is checked before each file and before each
+    /// level of the tree is built, returning `MerkleError::Cancelled` once it is set.
+    ///
+    /// If there is no file to hash at all, e.g. the paths are empty directories,
+    /// `MerkleError::EmptyInput` is returned.
+    pub fn from_paths_with_options<H, I, P>(
+        hasher: H,
+        paths: I,
+        options: &HashDirOptions,
+    ) -> Result<Self, MerkleError>
+    where
+        H: Hasher + 'static + std::marker::Sync + Clone,
+        I: IntoIterator<Item = P>,
+        P: AsRef<std::path::Path>,
+    {
+        let (paths, leaves) = fs::hash_dir_with_paths(hasher.clone(), paths, options)?
+            .into_iter()
+            .unzip();
+
+        let mut tree = Self::try_build(hasher, leaves, &TreeOptions::default(), options)?;
+        tree.paths = paths;
+
+        Ok(tree)
+    }
+
+    /// Construct a Merkletree from an iter of paths like `from_paths_with_options`, reading and
+    /// hashing up to `window_size` files at once.
+    ///
+    /// Only the leaf hashes and the contents of one window are held in memory, so peak memory
+    /// is bounded by `window_size` times the largest file. See `fs::hash_dir_windowed`.
+    ///
+    /// Like `from_paths_with_options`, `MerkleError::EmptyInput` is returned if there is no
+    /// file to hash.
+    pub fn from_paths_windowed<H, I, P>(
+        hasher: H,
+        paths: I,
+        window_size: usize,
+        options: &HashDirOptions,
+    ) -> Result<Self, MerkleError>
+    where
+        H: Hasher + 'static + std::marker::Sync + Clone,
+        I: IntoIterator<Item = P>,
+        P: AsRef<std::path::Path>,
+    {
+        let (paths, leaves) = fs::hash_dir_windowed(hasher.clone(), paths, window_size, options)?
+            .into_iter()
+            .unzip();
+
+        let mut tree = Self::try_build(hasher, leaves, &TreeOptions::default(), options)?;
+        tree.paths = paths;
+
+        Ok(tree)
+    }
+
+    /// Creates a new `MerkleTree` over the regular files of a tar archive, without extracting
+    /// it.
+    ///
+    /// Each file is streamed through the hasher into a leaf, and leaves are ordered by path
+    /// component by component, comparing names as raw bytes, like the filesystem walker
+    /// orders a directory. Directories, links and metadata entries are skipped. The path of
+    /// each file is recorded, see `paths`.
+    ///
+    /// # Returns
+    ///
+    /// The tree, `MerkleError::Io` if the archive cannot be read, or `MerkleError::EmptyInput`
+    /// if it contains no regular file.
+    #[cfg(feature = "tar")]
+    pub fn from_tar<R, H>(hasher: H, reader: R) -> Result<Self, MerkleError>
+    where
+        R: std::io::Read,
+        H: Hasher + 'static + std::marker::Sync,
+    {
+        // Errors not tied to an entry are reported with an empty path.
+        let to_error = |path: PathBuf| move |source| MerkleError::Io { path, source };
+
+        let mut archive = tar::Archive::new(reader);
+        let mut entries: Vec<(PathBuf, Node)> = Vec::new();
+        for entry in archive.entries().map_err(to_error(PathBuf::new()))? {
+            let entry = entry.map_err(to_error(PathBuf::new()))?;
+            if entry.header().entry_type() != tar::EntryType::Regular {
+                continue;
+            }
+
+            let path = entry.path().map_err(to_error(PathBuf::new()))?.into_owned();
+            let hash =
+                crate::hasher::hash_reader(&hasher, entry).map_err(to_error(path.clone()))?;
+            entries.push((path, Node::new_leaf(hash)));
+        }
+
+        entries.sort_by(|(a, _), (b, _)| {
+            let names = |path: &PathBuf| {
+                path.components()
+                    .map(|name| name.as_os_str().as_encoded_bytes().to_vec())
+                    .collect::<Vec<_>>()
+            };
+            names(a).cmp(&names(b))
+        });
+        let (paths, leaves): (Vec<PathBuf>, Vec<Node>) = entries.into_iter().unzip();
+
+        let mut tree = Self::try_build(
+            hasher,
+            leaves,
+            &TreeOptions::default(),
+            &HashDirOptions::default(),
+        )?;
+        tree.paths = paths;
+
+        Ok(tree)
+    }
+
+    /// Constructs the internal nodes of the tree from the leaves upward and computes the root.
+    fn build<H>(hasher: H, leaves: Vec<Node>) -> Self
+    where
+        H: Hasher + 'static + std::marker::Sync,
+    {
+        Self::try_build(
+            hasher,
+            leaves,
+            &TreeOptions::default(),
+            &HashDirOptions::default(),
+        )
+        .expect("build without cancellation flag cannot fail")
+    }
+
+    /// Like `build` with `tree_options`, checking the cancellation flag of `options` before
+    /// each level.
+    fn try_build<H>(
+        hasher: H,
+        mut leaves: Vec<Node>,
+        tree_options: &TreeOptions,
+        options: &HashDirOptions,
+    ) -> Result<Self, MerkleError>
+    where
+        H: Hasher + 'static + std::marker::Sync,
+    {
+        if leaves.is_empty() {
+            return Err(MerkleError::EmptyInput);
+        }
+
+        #[cfg(feature = "log")]
+        tree_options.warn_if_weak();
+
+        let original_leaves = leaves.clone();
+        let padding_hash = tree_options.padding_hash(&hasher);
+        let mut levels = Vec::new();
+        let mut height = 1;
+        #[cfg(feature = "trace")]
+        let mut trace = BuildTrace::default();
+
+        while leaves.len() > 1 {
+            options.check_cancelled()?;
+
+            if !leaves.len().is_multiple_of(2) {
+                let pad = match &padding_hash {
+                    Some(hash) => Node::new_leaf(hash.clone()),
+                    None => leaves.last().unwrap().clone(),
+                };
+                leaves.push(pad);
+            }
+
+            let next_level = tree_options.combine_pairs(&leaves, |left, right| {
+                let hash = hasher.combine(left.hash(), right.hash());
+                Node::new_internal(hash, left.clone(), right.clone())
+            });
+
+            #[cfg(feature = "verify-determinism")]
+            debug_assert!(
+                leaves
+                    .chunks(2)
+                    .map(|pair| hasher.combine(pair[0].hash(), pair[1].hash()))
+                    .eq(next_level.iter().map(|node| node.hash().to_string())),
+                "parallel build differs from the sequential one"
+            );
+
+            #[cfg(feature = "trace")]
+            for (pair, parent) in leaves.chunks(2).zip(&next_level) {
+                trace.record(height, pair[0].hash(), pair[1].hash(), parent.hash());
+            }
+
+            leaves = next_level;
+
+            levels.push(leaves.iter().map(|node| node.hash().to_string()).collect());
+            height += 1;
+        }
+
+        let root = leaves.into_iter().next().expect("root not found");
+
+        Ok(MerkleTree {
+            root_hash: tree_options.root_hash(&hasher, root.hash(), original_leaves.len()),
+            leaves: original_leaves,
+            height,
+            root,
+            levels,
+            paths: vec![],
+            options: *tree_options,
+            padding_hash,
+            aad: None,
+            #[cfg(feature = "trace")]
+            trace,
+        })
+    }
+
+    /// Detects which of the `candidates` algorithms produced `leaf_hash` from `sample_data`.
+    ///
+    /// This is useful to check the algorithm a loaded tree claims to use, given one of its
+    /// original items and the matching leaf hash.
+    ///
+    /// # Returns
+    ///
+    /// The first candidate whose hash of `sample_data` equals `leaf_hash`, or `None`.
+    pub fn detect_hasher(
+        sample_data: &[u8],
+        leaf_hash: &str,
+        candidates: &[HashAlgorithm],
+    ) -> Option<HashAlgorithm> {
+        candidates
+            .iter()
+            .copied()
+            .find(|algorithm| algorithm.hash(sample_data) == leaf_hash)
+    }
+
+    /// Returns the height of the tree, counted in levels (not edges): the leaves and the root
+    /// are both counted, so a single leaf has height `1`, two leaves `2`, and `n` leaves
+    /// `ceil(log2(n)) + 1`. A proof has `height() - 1` steps.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns true if the tree has no leaves (should never happen if `new()` was used).
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of leaf nodes in the tree.
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Returns the tree' leaves.
+    ///
+    /// Only the real leaves are returned, one per input item, so the result always has `len()`
+    /// elements. Use [`MerkleTree::padded_leaves`] to include the padding duplicate.
+    pub fn leaves(&self) -> Vec<Node> {
+        self.leaves.clone()
+    }
+
+    /// Returns the last real leaf of the tree, ignoring any padding duplicate.
+    pub fn last_leaf(&self) -> Option<&Node> {
+        self.leaves.last()
+    }
+
+    /// Returns a reference to the tree's leaves, without cloning them.
+    pub fn leaves_ref(&self) -> &[Node] {
+        &self.leaves
+    }
+
+    /// Returns the indices of all the leaves whose hash starts with the bytes of `prefix`, in
+    /// order. This scans every leaf, so it is O(n).
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - The leading bytes of the hash, e.g. `[0xab, 0xcd, 0x12, 0x34]` for a
+    ///   hash that starts with `abcd1234`.
+    pub fn find_by_hash_prefix(&self, prefix: &[u8]) -> Vec<usize> {
+        let prefix = hex::encode(prefix);
+
+        self.leaves
+            .iter()
+            .enumerate()
+            .filter(|(_, leaf)| leaf.hash().starts_with(&prefix))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Returns the leaf at `index` with its proof and the root, read from the hashes retained
+    /// by the tree in a single walk up its path.
+    ///
+    /// # Returns
+    ///
+    /// The entry, or `None` if `index` is not a leaf of the tree.
+    pub fn entry(&self, index: usize) -> Option<Entry> {
+        let proof = self.generate_proof(index)?;
+
+        Some(Entry {
+            leaf_index: index,
+            leaf_hash: self.leaves[index].hash().to_string(),
+            proof,
+            root: self.root_hash.clone(),
+        })
+    }
+
+    /// Returns the path of the file behind each leaf, in leaf order.
+    ///
+    /// Only trees built with `from_paths` or `from_paths_with_options` retain paths; for other
+    /// trees the result is empty.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Returns the leaves as they are paired when building the first internal level.
+    ///
+    /// If the number of leaves is odd (and greater than one), the last leaf is padded according
+    /// to the tree's `PaddingStrategy`, so the result has an even number of elements.
+    pub fn padded_leaves(&self) -> Vec<Node> {
+        let mut leaves = self.leaves.clone();
+        if leaves.len() > 1 && !leaves.len().is_multiple_of(2) {
+            leaves.push(Node::new_leaf(
+                self.paired_hash(0, leaves.len()).to_string(),
+            ));
+        }
+
+        leaves
+    }
+
+    /// Returns the hashes of `level`, `0` being the leaves and `height() - 1` the root.
+    ///
+    /// Unlike `leaves`, an odd level (other than the root) includes the padding hash that was
+    /// used to pair its last hash, so the hashes of the level above are exactly the
+    /// combinations of consecutive pairs.
+    ///
+    /// # Returns
+    ///
+    /// `None` if `level` is not below `height()`.
+    pub fn level_hashes(&self, level: usize) -> Option<Vec<String>> {
+        if level >= self.height {
+            return None;
+        }
+
+        let len = self.level_len(level);
+        let mut hashes: Vec<String> = (0..len)
+            .map(|position| self.level_hash(level, position).to_string())
+            .collect();
+        if len > 1 && !len.is_multiple_of(2) {
+            hashes.push(self.paired_hash(level, len).to_string());
+        }
+
+        Some(hashes)
+    }
+
+    /// Returns the root node of the tree.
+    ///
+    /// When the leaf count is committed or context is bound, the node's hash is the published
+    /// root rather than the combination of its children.
+    pub fn root(&self) -> Node {
+        let mut root = self.root.clone();
+        root.set_hash(self.root_hash.clone());
+        root
+    }
+
+    /// Returns the root hash as a lowercase hex string, without cloning the root node.
+    pub fn root_hex(&self) -> String {
+        self.root_hash.clone()
+    }
+
+    /// Writes the root hash as lowercase hex into `buf` without allocating.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the root hash is not `N` hex characters long, e.g. 64 for a 32-byte digest.
+    pub fn write_root_hex<const N: usize>(&self, buf: &mut [u8; N]) {
+        buf.copy_from_slice(self.root_hash.as_bytes());
+    }
+
+    /// Estimates the number of bytes the tree occupies, heap allocations included.
+    ///
+    /// This sums the tree itself, the leaves, every node reachable from the root (each child
+    /// is a separate `Box` allocation, padding duplicates included), the retained level
+    /// hashes and the recorded paths. String and vector capacities are counted, but allocator
+    /// overhead and alignment are not, so the actual usage is somewhat higher.
+    pub fn memory_footprint(&self) -> usize {
+        use std::mem::size_of;
+
+        let mut bytes = size_of::<Self>() + self.root_hash.capacity();
+
+        bytes += self.leaves.capacity() * size_of::<Node>();
+        bytes += self
+            .leaves
+            .iter()
+            .map(|leaf| leaf.hash().len())
+            .sum::<usize>();
+
+        bytes += self.root.hash().len();
+        bytes += self
+            .root
+            .iter()
+            .skip(1)
+            .map(|node| size_of::<Node>() + node.hash().len())
+            .sum::<usize>();
+
+        bytes += self.levels.capacity() * size_of::<Vec<String>>();
+        for level in &self.levels {
+            bytes += level.capacity() * size_of::<String>();
+            bytes += level.iter().map(String::capacity).sum::<usize>();
+        }
+
+        bytes += self.paths.capacity() * size_of::<PathBuf>();
+        bytes += self
+            .paths
+            .iter()
+            .map(|path| path.as_os_str().len())
+            .sum::<usize>();
+        bytes += self.aad.as_ref().map_or(0, Vec::capacity);
+
+        bytes
+    }
+
+    /// Returns the options the tree was built with.
+    pub fn options(&self) -> &TreeOptions {
+        &self.options
+    }
+
+    /// Binds the tree to external context, such as an epoch or a policy ID, as a terminal step
+    /// of the root: `root' = H(root || aad)`, after the length commitment if any. Leaf hashing
+    /// is unchanged, and proofs only verify within that context, through
+    /// `DefaultProofer::verify_with_aad`.
+    ///
+    /// Replaces any context bound before. The context is kept across leaf updates.
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - The hasher the tree was built with.
+    /// * `aad` - The context, e.g. a big-endian epoch number.
+    pub fn with_aad<H>(mut self, hasher: &H, aad: &[u8]) -> Self
+    where
+        H: Hasher,
+    {
+        self.aad = Some(aad.to_vec());
+        self.root_hash = self.published_root(hasher, self.root.hash());
+        self
+    }
+
+    /// Returns every combine made when the tree was built, for an auditor to replay with
+    /// `BuildTrace::replay`. Later leaf updates are not recorded.
+    #[cfg(feature = "trace")]
+    pub fn build_trace(&self) -> &BuildTrace {
+        &self.trace
+    }
+
+    /// Returns the context bound to the root with `with_aad`, if any.
+    pub fn aad(&self) -> Option<&[u8]> {
+        self.aad.as_deref()
+    }
+
+    /// Returns the published root of the tree whose top node hashes to `top`.
+    fn published_root<H>(&self, hasher: &H, top: &str) -> String
+    where
+        H: Hasher,
+    {
+        let root = self.options.root_hash(hasher, top, self.leaves.len());
+        match &self.aad {
+            Some(aad) => aad_root(hasher, &root, aad),
+            None => root,
+        }
+    }
+
+    /// Returns the nodes of every level, from the leaves up to the root, without padding, as
+    /// stored by `DefaultProofer`. Nodes are cloned from the tree, nothing is hashed.
+    pub(crate) fn node_levels(&self) -> Vec<Vec<Node>> {
+        let mut levels = Vec::with_capacity(self.height);
+        let mut frontier = vec![&self.root];
+
+        for level in (0..self.height).rev() {
+            frontier.truncate(self.level_len(level));
+            levels.push(frontier.iter().map(|node| (*node).clone()).collect());
+            frontier = frontier
+                .iter()
+                .flat_map(|node| [node.status().left(), node.status().right()])
+                .flatten()
+                .collect();
+        }
+
+        levels.reverse();
+        levels
+    }
+
+    /// Marks the leaf at `index` as deleted by replacing it with the tombstone hash.
+    ///
+    /// Only the hashes on the path from the leaf to the root are recomputed. A proof for the
+    /// deleted leaf verifies against `TreeOptions::tombstone_hash`, so a verifier can tell that
+    /// the leaf was explicitly revoked. The tree must be built with `TreeOptions::revocable`,
+    /// otherwise the tombstone could be the hash of some real data.
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - The hasher the tree was built with.
+    /// * `index` - Index of the leaf to delete.
+    ///
+    /// # Returns
+    ///
+    /// `MerkleError::NotRevocable` if the tree is not revocable, or
+    /// `MerkleError::IndexOutOfBounds` if `index` is not a leaf of the tree.
+    pub fn delete_leaf<H>(&mut self, hasher: &H, index: usize) -> Result<(), MerkleError>
+    where
+        H: Hasher,
+    {
+        let tombstone = self
+            .options
+            .tombstone_hash(hasher, index)
+            .ok_or(MerkleError::NotRevocable)?;
+        self.replace_leaf_hash(hasher, index, tombstone)
+    }
+
+    /// Replaces the data of the leaf at `index`, hashing it as the tree's options do.
+    ///
+    /// Only the hashes on the path from the leaf to the root are recomputed.
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - The hasher the tree was built with.
+    /// * `index` - Index of the leaf to update.
+    /// * `data` - The new data of the leaf.
+    ///
+    /// # Returns
+    ///
+    /// `MerkleError::IndexOutOfBounds` if `index` is not a leaf of the tree.
+    pub fn update_leaf<H>(
+        &mut self,
+        hasher: &H,
+        index: usize,
+        data: &[u8],
+    ) -> Result<(), MerkleError>
+    where
+        H: Hasher,
+    {
+        let hash = self.options.leaf_hash(hasher, index, data);
+        self.replace_leaf_hash(hasher, index, hash)
+    }
+
+    /// Replaces the data of several leaves at once, as `update_leaf` would one by one.
+    ///
+    /// Every leaf is updated first, then the internal nodes above them are recomputed level by
+    /// level, each exactly once, so updates sharing ancestors cost far less than sequential
+    /// `update_leaf` calls. If an index appears several times, its last update wins.
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - The hasher the tree was built with.
+    /// * `updates` - Pairs of leaf index and new data.
+    ///
+    /// # Returns
+    ///
+    /// `MerkleError::IndexOutOfBounds` if an index is not a leaf of the tree, in which case
+    /// the tree is left untouched.
+    pub fn update_leaves<H>(
+        &mut self,
+        hasher: &H,
+        updates: &[(usize, Vec<u8>)],
+    ) -> Result<(), MerkleError>
+    where
+        H: Hasher,
+    {
+        if let Some(&(index, _)) = updates
+            .iter()
+            .find(|(index, _)| *index >= self.leaves.len())
+        {
+            return Err(MerkleError::IndexOutOfBounds {
+                index,
+                len: self.leaves.len(),
+            });
+        }
+        if updates.is_empty() {
+            return Ok(());
+        }
+
+        for (index, data) in updates {
+            self.leaves[*index] = Node::new_leaf(self.options.leaf_hash(hasher, *index, data));
+        }
+
+        let mut dirty: Vec<usize> = updates.iter().map(|(index, _)| *index).collect();
+        dirty.sort_unstable();
+        dirty.dedup();
+        let leaves = dirty.clone();
+
+        for level in 1..self.height {
+            dirty = dirty.iter().map(|position| position >> 1).collect();
+            dirty.dedup();
+            for &position in &dirty {
+                let combined = hasher.combine(
+                    self.level_hash(level - 1, 2 * position),
+                    self.paired_hash(level - 1, 2 * position + 1),
+                );
+                self.levels[level - 1][position] = combined;
+            }
+        }
+
+        let mut root = std::mem::replace(&mut self.root, Node::new_leaf(String::new()));
+        for index in leaves {
+            self.refresh_node(&mut root, index);
+        }
+        self.root_hash = self.published_root(hasher, root.hash());
+        self.root = root;
+
+        Ok(())
+    }
+
+    /// Generates a proof for the leaf at `index` from the hashes retained by the tree,
+    /// identical to the one `DefaultProofer` would generate.
+    pub(crate) fn generate_proof(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.leaves.len() {
+            return None;
+        }
+
+        let path = (0..self.height - 1)
+            .map(|level| {
+                let position = index >> level;
+                let sibling = position ^ 1;
+                ProofNode {
+                    hash: self.paired_hash(level, sibling).to_string(),
+                    child_type: if sibling < position {
+                        NodeChildType::Left
+                    } else {
+                        NodeChildType::Right
+                    },
+                }
+            })
+            .collect();
+
+        Some(MerkleProof {
+            path,
+            leaf_index: index,
+            leaf_hash: Some(self.leaves[index].hash().to_string()),
+        })
+    }
+
+    /// Replaces the hash of the leaf at `index` and recomputes its path up to the root.
+    pub(crate) fn replace_leaf_hash<H>(
+        &mut self,
+        hasher: &H,
+        index: usize,
+        hash: String,
+    ) -> Result<(), MerkleError>
+    where
+        H: Hasher,
+    {
+        if index >= self.leaves.len() {
+            return Err(MerkleError::IndexOutOfBounds {
+                index,
+                len: self.leaves.len(),
+            });
+        }
+
+        self.leaves[index] = Node::new_leaf(hash);
+        for level in 1..self.height {
+            let left = 2 * (index >> level);
+            let combined = hasher.combine(
+                self.level_hash(level - 1, left),
+                self.paired_hash(level - 1, left + 1),
+            );
+            self.levels[level - 1][index >> level] = combined;
+        }
+
+        let mut root = std::mem::replace(&mut self.root, Node::new_leaf(String::new()));
+        self.refresh_node(&mut root, index);
+        self.root_hash = self.published_root(hasher, root.hash());
+        self.root = root;
+
+        Ok(())
+    }
+
+    /// Number of nodes at `level`, without padding.
+    fn level_len(&self, level: usize) -> usize {
+        match level {
+            0 => self.leaves.len(),
+            _ => self.levels[level - 1].len(),
+        }
+    }
+
+    /// Hash of the node at `position` within `level`.
+    fn level_hash(&self, level: usize, position: usize) -> &str {
+        match level {
+            0 => self.leaves[position].hash(),
+            _ => &self.levels[level - 1][position],
+        }
+    }
+
+    /// Hash paired at `position` within `level`: the stored hash, or past the last node the
+    /// padding hash, or a duplicate of the last node.
+    fn paired_hash(&self, level: usize, position: usize) -> &str {
+        if position < self.level_len(level) {
+            return self.level_hash(level, position);
+        }
+
+        match &self.padding_hash {
+            Some(hash) => hash,
+            None => self.level_hash(level, position - 1),
+        }
+    }
+
+    /// Copies the stored hashes into the nodes on the path from `root` down to the leaf at
+    /// `index`, walking the path iteratively.
+    fn refresh_node(&self, root: &mut Node, index: usize) {
+        let top = self.height - 1;
+        let mut padded = Vec::new();
+
+        let mut node = &mut *root;
+        for level in (1..=top).rev() {
+            node.set_hash(self.level_hash(level, index >> level).to_string());
+
+            let child = index >> (level - 1);
+            if self.padding_hash.is_none()
+                && child.is_multiple_of(2)
+                && child + 1 >= self.level_len(level - 1)
+            {
+                padded.push(level);
+            }
+            let (left, right) = node.children_mut().expect("internal node above the leaves");
+            node = if child % 2 == 1 { right } else { left };
+        }
+        node.set_hash(self.level_hash(0, index).to_string());
+
+        // When duplicating, the right child of a padded node is a copy of the left one: refresh
+        // the copies from the bottom up, once the left subtrees below them are up to date.
+        for &level in padded.iter().rev() {
+            let mut node = &mut *root;
+            for above in (level + 1..=top).rev() {
+                let (left, right) = node.children_mut().expect("internal node above the leaves");
+                node = if (index >> (above - 1)) % 2 == 1 {
+                    right
+                } else {
+                    left
+                };
+            }
+            let (left, right) = node.children_mut().expect("internal node above the leaves");
+            *right = left.clone();
+        }
+    }
+}
+
+/// Folds a level of hashes in place, pairing them up (duplicating the last one if the level
+/// is odd) until only the root is left.
+fn fold_hashes<H>(hasher: &H, mut level: Vec<String>) -> Option<String>
+where
+    H: Hasher,
+{
+    while level.len() > 1 {
+        if !level.len().is_multiple_of(2) {
+            level.push(level.last().unwrap().clone());
+        }
+
+        for i in 0..level.len() / 2 {
+            level[i] = hasher.combine(&level[2 * i], &level[2 * i + 1]);
+        }
+        level.truncate(level.len() / 2);
+    }
+
+    level.pop()
+}
+
+/// Returns the maximum number of leaves of a tree of `height` levels, `2^(height - 1)`.
+///
+/// A height of `0` holds no leaves.
+///
+/// # Panics
+///
+/// Panics if the capacity does not fit in a `usize`.
+pub fn capacity_for_height(height: usize) -> usize {
+    match height {
+        0 => 0,
+        _ => u32::try_from(height - 1)
+            .ok()
+            .and_then(|shift| 1usize.checked_shl(shift))
+            .expect("capacity does not fit in a usize"),
+    }
+}
+
+/// Returns the height of the tree built over `leaves` leaves, padding included: `1` for a
+/// single leaf, then one more level each time the leaf count passes a power of two. This is
+/// the inverse of `capacity_for_height`.
+///
+/// No tree can be built over `0` leaves, whose height is `0`.
+pub fn height_for_leaves(leaves: usize) -> usize {
+    match leaves {
+        0 => 0,
+        _ => leaves.next_power_of_two().trailing_zeros() as usize + 1,
+    }
+}
+
+/// Builds a tree over `data` together with a `DefaultProofer` for it.
+///
+/// The proofer reuses the levels computed for the tree instead of hashing everything again,
+/// as `DefaultProofer::new(hasher, tree.leaves())` would.
+///
+/// # Panics
+///
+/// Panics if `data` is empty.
+pub fn build_tree_and_proofer<I, T, H>(hasher: H, data: I) -> (MerkleTree, DefaultProofer<H>)
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+    H: Hasher + 'static + std::marker::Sync + Clone,
+{
+    let tree = MerkleTree::new(hasher.clone(), data);
+    let proofer = DefaultProofer::from_tree(hasher, &tree);
+
+    (tree, proofer)
+}
+
+/// Computes the root hash of the tree over `data` without building any `Node`.
+///
+/// The result is the same as `MerkleTree::new(hasher, data).root().hash()`, but only the leaf
+/// hashes are allocated and each level is folded in place.
+///
+/// # Returns
+///
+/// The root hash, or `None` if `data` is empty.
+pub fn compute_root<I, T, H>(hasher: &H, data: I) -> Option<String>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+    H: Hasher,
+{
+    let leaves: Vec<String> = data
+        .into_iter()
+        .map(|item| hasher.hash(item.as_ref()))
+        .collect();
+
+    fold_hashes(hasher, leaves)
+}
+
+/// Computes the root hash over leaves that are already hashed, without building any `Node`.
+///
+/// The leaf hashes are copied once into a scratch buffer that each level is folded into, so
+/// this is the cheapest way to get a root when only the leaf hashes are at hand.
+///
+/// # Returns
+///
+/// The root hash, or `None` if `hashes` is empty.
+pub fn root_from_hashes<H, S>(hasher: &H, hashes: &[S]) -> Option<String>
+where
+    H: Hasher,
+    S: AsRef<str>,
+{
+    fold_hashes(
+        hasher,
+        hashes
+            .iter()
+            .map(|hash| hash.as_ref().to_string())
+            .collect(),
+    )
+}
+
+/// Returns `true` if the whole dataset hashes to `expected_root`.
+///
+/// The root is rebuilt with [`compute_root`], so no tree structure is kept around. An empty
+/// dataset never matches.
+pub fn verify_dataset<I, T, H>(hasher: &H, data: I, expected_root: &str) -> bool
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+    H: Hasher,
+{
+    compute_root(hasher, data).is_some_and(|root| root == expected_root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hasher::*;
+    #[cfg(feature = "sha256")]
+    use crate::proof::Proofer;
+
+    #[test]
+    fn test_merkle_tree_with_default_hasher() {
+        let data = &["hello".as_bytes(), "world".as_bytes()];
+        let tree = MerkleTree::new(DummyHasher, data);
+
+        assert_eq!(tree.height(), 2);
+        assert_eq!(tree.root().hash(), "hash_539");
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_merkle_tree_hashing() {
+        let data = &["hello".as_bytes(), "world".as_bytes()];
+        let tree = MerkleTree::new(SHA256Hasher::new(), data);
+
+        assert_eq!(tree.height(), 2);
+        assert_eq!(
+            tree.root().hash(),
+            "15e178b71fae8849ee562c9cc0d7ea322fba6cd495411329d47234479167cc8b"
+        );
+    }
+
+    #[cfg(feature = "sha256")]
     #[test]
     fn test_merkle_tree_single_leaf() {
         let data = &["hello".as_bytes()];
         let tree = MerkleTree::new(SHA256Hasher::new(), data);
 
-        assert_eq!(tree.height(), 1);
-        assert_eq!(tree.len(), 1);
+        assert_eq!(tree.height(), 1);
+        assert_eq!(tree.len(), 1);
+        assert_eq!(
+            tree.root().hash(),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_merkle_tree_with_10_elements() {
+        let inputs = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"];
+        let data: Vec<&[u8]> = inputs.iter().map(|s| s.as_bytes()).collect();
+
+        let tree = MerkleTree::new(SHA256Hasher::new(), &data);
+
+        assert_eq!(tree.height(), 5); // 10 elements padded to 16 → log2(16) + 1 = 5
+
+        // You can print the root hash if you're unsure what it should be:
+        println!("Merkle root hash: {}", tree.root().hash());
+
+        // If you know the expected hash, use:
+        assert_eq!(
+            tree.root().hash(),
+            "9da1ff0dfa79217bdbea9ec96407b1e693646cc493f64059fa27182a37cadf94"
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_merkle_tree_from_lazy_iterator() {
+        let owned: Vec<Vec<u8>> = (0u8..10).map(|i| vec![i; 1024]).collect();
+        let lazy = (0u8..10).map(|i| vec![i; 1024]);
+
+        let expected = MerkleTree::new(SHA256Hasher::new(), &owned);
+        let tree = MerkleTree::new(SHA256Hasher::new(), lazy);
+
+        assert_eq!(tree.len(), 10);
+        assert_eq!(tree.root().hash(), expected.root().hash());
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_merkle_tree_root_hex() {
+        let data = &["hello".as_bytes(), "world".as_bytes()];
+        let tree = MerkleTree::new(SHA256Hasher::new(), data);
+
+        let mut buf = [0u8; 64];
+        tree.write_root_hex(&mut buf);
+
+        assert_eq!(tree.root_hex(), tree.root().hash());
+        assert_eq!(&buf[..], tree.root().hash().as_bytes());
+
+        let tree = MerkleTree::new(TruncatingHasher::<_, 20>::new(SHA256Hasher::new()), data);
+        let mut buf = [0u8; 40];
+        tree.write_root_hex(&mut buf);
+        assert_eq!(&buf[..], tree.root_hex().as_bytes());
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_merkle_tree_from_array() {
+        let data = ["hello".as_bytes(), "world".as_bytes()];
+        let tree = MerkleTree::from_array(SHA256Hasher::new(), data);
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(
+            tree.root().hash(),
+            MerkleTree::new(SHA256Hasher::new(), data).root().hash()
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    #[should_panic(expected = "Merkle Tree requires at least one element")]
+    fn test_merkle_tree_from_empty_array() {
+        let data: [&[u8]; 0] = [];
+        MerkleTree::from_array(SHA256Hasher::new(), data);
+    }
+
+    #[cfg(all(feature = "tokio", feature = "sha256"))]
+    #[tokio::test]
+    async fn test_merkle_tree_from_async_readers() {
+        let data = ["hello".as_bytes(), "world".as_bytes()];
+        let tree = MerkleTree::from_async_readers(SHA256Hasher::new(), data)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            tree.root().hash(),
+            "15e178b71fae8849ee562c9cc0d7ea322fba6cd495411329d47234479167cc8b"
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_merkle_tree_leaves_and_padded_leaves() {
+        for (count, padded) in [(1, 1), (2, 2), (3, 4), (4, 4), (5, 6)] {
+            let data: Vec<Vec<u8>> = (0..count).map(|i| vec![i as u8]).collect();
+            let tree = MerkleTree::new(SHA256Hasher::new(), &data);
+
+            assert_eq!(tree.leaves().len(), count);
+            assert_eq!(tree.len(), count);
+            assert_eq!(tree.padded_leaves().len(), padded);
+        }
+
+        let data = &["a".as_bytes(), "b".as_bytes(), "c".as_bytes()];
+        let tree = MerkleTree::new(SHA256Hasher::new(), data);
+        let padded = tree.padded_leaves();
+        assert_eq!(padded[3].hash(), padded[2].hash());
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_merkle_tree_build_sharded() {
+        for count in [1, 2, 3, 5, 8, 13, 17] {
+            let data: Vec<Vec<u8>> = (0..count).map(|i| vec![i as u8]).collect();
+            let expected = MerkleTree::new(SHA256Hasher::new(), &data);
+
+            for shards in [1, 2, 3, 4, 16] {
+                let (tree, shard_roots) =
+                    MerkleTree::build_sharded(SHA256Hasher::new(), &data, shards);
+
+                assert!(shard_roots.len() <= shards);
+                assert_eq!(tree.root().hash(), expected.root().hash());
+                assert_eq!(tree.height(), expected.height());
+                assert_eq!(tree.len(), expected.len());
+            }
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_merkle_tree_from_paths_with_options() {
+        use std::sync::atomic::AtomicBool;
+
+        let hasher = SHA256Hasher::new();
+        let tree = MerkleTree::from_paths_with_options(
+            hasher.clone(),
+            ["tests/pics"],
+            &HashDirOptions::new(),
+        )
+        .unwrap();
         assert_eq!(
             tree.root().hash(),
-            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+            MerkleTree::from_paths(hasher.clone(), ["tests/pics"])
+                .root()
+                .hash()
         );
+
+        let cancel = AtomicBool::new(true);
+        let result = MerkleTree::from_paths_with_options(
+            hasher,
+            ["tests/pics"],
+            &HashDirOptions::new().cancel(&cancel),
+        );
+        assert!(matches!(result, Err(MerkleError::Cancelled)));
     }
 
+    #[cfg(feature = "sha256")]
     #[test]
-    fn test_merkle_tree_with_10_elements() {
-        let inputs = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"];
-        let data: Vec<&[u8]> = inputs.iter().map(|s| s.as_bytes()).collect();
+    fn test_merkle_tree_from_empty_paths() {
+        let root = std::env::temp_dir().join(format!("mt-empty-paths-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
 
-        let tree = MerkleTree::new(SHA256Hasher::new(), &data);
+        let hasher = SHA256Hasher::new();
+        let options = HashDirOptions::new();
+        let from_dir = MerkleTree::from_paths_with_options(hasher.clone(), [&root], &options);
+        let windowed = MerkleTree::from_paths_windowed(hasher.clone(), [&root], 4, &options);
+        std::fs::remove_dir_all(&root).unwrap();
 
-        assert_eq!(tree.height(), 5); // 10 elements padded to 16 → log2(16) + 1 = 5
+        assert!(matches!(from_dir, Err(MerkleError::EmptyInput)));
+        assert!(matches!(windowed, Err(MerkleError::EmptyInput)));
+        assert!(matches!(
+            MerkleTree::from_paths_with_options(hasher, Vec::<PathBuf>::new(), &options),
+            Err(MerkleError::EmptyInput)
+        ));
+    }
 
-        // You can print the root hash if you're unsure what it should be:
-        println!("Merkle root hash: {}", tree.root().hash());
+    #[cfg(all(feature = "sha256", feature = "keccak256", feature = "blake3"))]
+    #[test]
+    fn test_merkle_tree_detect_hasher() {
+        let tree = MerkleTree::new(Keccak256Hasher::new(), ["hello", "world"]);
+        let leaf_hash = tree.leaves()[0].hash().to_string();
 
-        // If you know the expected hash, use:
         assert_eq!(
-            tree.root().hash(),
-            "9da1ff0dfa79217bdbea9ec96407b1e693646cc493f64059fa27182a37cadf94"
+            MerkleTree::detect_hasher(b"hello", &leaf_hash, HashAlgorithm::all()),
+            Some(HashAlgorithm::Keccak256)
+        );
+        assert_eq!(
+            MerkleTree::detect_hasher(
+                b"hello",
+                &leaf_hash,
+                &[HashAlgorithm::Sha256, HashAlgorithm::Blake3]
+            ),
+            None
+        );
+        assert_eq!(
+            MerkleTree::detect_hasher(b"world", &leaf_hash, HashAlgorithm::all()),
+            None
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_compute_root_and_verify_dataset() {
+        let hasher = SHA256Hasher::new();
+        for count in 1..=17 {
+            let data: Vec<Vec<u8>> = (0..count).map(|i| vec![i as u8]).collect();
+            let tree = MerkleTree::new(hasher.clone(), &data);
+
+            assert_eq!(compute_root(&hasher, &data).unwrap(), tree.root().hash());
+            assert!(verify_dataset(&hasher, &data, tree.root().hash()));
+            assert!(!verify_dataset(&hasher, &data[1..], tree.root().hash()));
+        }
+
+        let empty: [&[u8]; 0] = [];
+        assert!(compute_root(&hasher, empty).is_none());
+        assert!(!verify_dataset(&hasher, empty, ""));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_delete_leaf_recomputes_root() {
+        use crate::proof::{DefaultProofer, Proofer};
+
+        let hasher = SHA256Hasher::new();
+        let options = TreeOptions::new().revocable(true);
+        for count in 1..=9 {
+            let data: Vec<Vec<u8>> = (0..count).map(|i| vec![i as u8]).collect();
+            for index in 0..count {
+                let mut tree =
+                    MerkleTreeBuilder::with_options(hasher.clone(), options).build(&data);
+                tree.delete_leaf(&hasher, index).unwrap();
+
+                let tombstone = options.tombstone_hash(&hasher, index).unwrap();
+                let mut hashes: Vec<String> = data
+                    .iter()
+                    .map(|item| hasher.hash(&[LEAF_DOMAIN, item].concat()))
+                    .collect();
+                hashes[index] = tombstone.clone();
+                assert_eq!(tree.root_hex(), fold_hashes(&hasher, hashes).unwrap());
+                assert!(tree.root().verify_subtree(&hasher));
+
+                let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+                let proof = proofer.generate(index).unwrap();
+                assert!(proofer.verify_hash(&proof, tombstone, &tree.root_hex()));
+                assert!(!proofer.verify(&proof, &data[index], &tree.root_hex()));
+            }
+        }
+
+        let mut tree = MerkleTreeBuilder::with_options(hasher.clone(), options).build(["a", "b"]);
+        assert!(matches!(
+            tree.delete_leaf(&hasher, 2),
+            Err(MerkleError::IndexOutOfBounds { index: 2, len: 2 })
+        ));
+
+        let mut tree = MerkleTree::new(hasher.clone(), ["a", "b"]);
+        assert!(matches!(
+            tree.delete_leaf(&hasher, 0),
+            Err(MerkleError::NotRevocable)
+        ));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_data_cannot_forge_a_tombstone() {
+        let hasher = SHA256Hasher::new();
+        for options in [
+            TreeOptions::new().revocable(true),
+            TreeOptions::new().revocable(true).bind_index(true),
+            TreeOptions::new()
+                .revocable(true)
+                .leaf_salt_mode(LeafSaltMode::Fixed([7; 32])),
+        ] {
+            let tombstone = options.tombstone_hash(&hasher, 0).unwrap();
+            let tree = MerkleTreeBuilder::with_options(hasher.clone(), options)
+                .build([TOMBSTONE_DOMAIN, b"b"]);
+            assert_ne!(tree.leaves_ref()[0].hash(), tombstone);
+            assert_ne!(options.leaf_hash(&hasher, 0, TOMBSTONE_DOMAIN), tombstone);
+            assert_ne!(options.leaf_hash(&hasher, 0, b""), tombstone);
+        }
+
+        // Tombstones follow the salt and the index binding of the tree.
+        let salted = TreeOptions::new()
+            .revocable(true)
+            .leaf_salt_mode(LeafSaltMode::Fixed([7; 32]));
+        let bound = TreeOptions::new().revocable(true).bind_index(true);
+        let plain = TreeOptions::new().revocable(true);
+        assert_ne!(
+            salted.tombstone_hash(&hasher, 0),
+            plain.tombstone_hash(&hasher, 0)
+        );
+        assert_ne!(
+            bound.tombstone_hash(&hasher, 0),
+            bound.tombstone_hash(&hasher, 1)
+        );
+        assert_eq!(TreeOptions::new().tombstone_hash(&hasher, 0), None);
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_update_leaves_matches_fresh_build() {
+        let hasher = SHA256Hasher::new();
+        let data: Vec<Vec<u8>> = (0..11u8).map(|i| vec![i]).collect();
+        let updates: Vec<(usize, Vec<u8>)> = vec![
+            (0, b"x".to_vec()),
+            (3, b"y".to_vec()),
+            (2, b"z".to_vec()),
+            (10, b"w".to_vec()),
+            (3, b"v".to_vec()),
+        ];
+
+        for padding in [PaddingStrategy::Duplicate, PaddingStrategy::HashEmpty] {
+            let options = TreeOptions::new().bind_index(true).padding(padding);
+            let build = |data: &[Vec<u8>]| {
+                MerkleTreeBuilder::with_options(hasher.clone(), options).build(data)
+            };
+
+            let mut expected_data = data.clone();
+            for (index, item) in &updates {
+                expected_data[*index] = item.clone();
+            }
+            let expected = build(&expected_data);
+
+            let mut tree = build(&data);
+            tree.update_leaves(&hasher, &updates).unwrap();
+            assert_eq!(tree.root_hex(), expected.root_hex());
+            let hashes = |tree: &MerkleTree| -> Vec<Vec<String>> {
+                tree.node_levels()
+                    .iter()
+                    .map(|level| level.iter().map(|node| node.hash().to_string()).collect())
+                    .collect()
+            };
+            assert_eq!(hashes(&tree), hashes(&expected));
+            assert!(tree.root().verify_subtree(&hasher));
+
+            let mut sequential = build(&data);
+            for (index, item) in &updates {
+                sequential.update_leaf(&hasher, *index, item).unwrap();
+            }
+            assert_eq!(sequential.root_hex(), expected.root_hex());
+        }
+
+        let mut tree = MerkleTree::new(hasher.clone(), &data);
+        let root = tree.root_hex();
+        assert!(matches!(
+            tree.update_leaves(&hasher, &[(1, vec![]), (11, vec![])]),
+            Err(MerkleError::IndexOutOfBounds { index: 11, len: 11 })
+        ));
+        assert_eq!(tree.root_hex(), root);
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_from_matrix_proves_cell_by_row_and_column() {
+        use crate::proof::{DefaultProofer, Proofer};
+
+        let hasher = SHA256Hasher::new();
+        let rows: Vec<Vec<Vec<u8>>> = (0..3)
+            .map(|r| (0..5).map(|c| vec![r, c]).collect())
+            .collect();
+        let (row_tree, column_tree) = MerkleTree::from_matrix(hasher.clone(), &rows);
+
+        assert_eq!(row_tree.len(), 3);
+        assert_eq!(column_tree.len(), 5);
+
+        let (r, c) = (2, 3);
+        let cell = &rows[r][c];
+
+        let row = MerkleTree::new(hasher.clone(), &rows[r]);
+        let row_proofer = DefaultProofer::new(hasher.clone(), row.leaves());
+        assert!(row_proofer.verify(&row_proofer.generate(c).unwrap(), cell, &row.root_hex()));
+        let rows_proofer = DefaultProofer::new(hasher.clone(), row_tree.leaves());
+        let proof = rows_proofer.generate(r).unwrap();
+        assert!(rows_proofer.verify_hash(&proof, row.root_hex(), &row_tree.root_hex()));
+
+        let column = MerkleTree::new(hasher.clone(), rows.iter().map(|row| &row[c]));
+        let column_proofer = DefaultProofer::new(hasher.clone(), column.leaves());
+        let proof = column_proofer.generate(r).unwrap();
+        assert!(column_proofer.verify(&proof, cell, &column.root_hex()));
+        let columns_proofer = DefaultProofer::new(hasher.clone(), column_tree.leaves());
+        let proof = columns_proofer.generate(c).unwrap();
+        assert!(columns_proofer.verify_hash(&proof, column.root_hex(), &column_tree.root_hex()));
+    }
+
+    #[test]
+    fn test_from_leaves_hashes_encoding() {
+        use std::borrow::Cow;
+
+        struct Pair(u8, u8);
+        impl Leaf for Pair {
+            fn encode(&self) -> Cow<'_, [u8]> {
+                Cow::Owned(vec![self.0, self.1])
+            }
+        }
+
+        let typed = MerkleTree::from_leaves(DummyHasher, [Pair(1, 2), Pair(3, 4)]);
+        let raw = MerkleTree::new(DummyHasher, [[1u8, 2], [3, 4]]);
+        assert_eq!(typed.root_hex(), raw.root_hex());
+
+        let bytes = MerkleTree::from_leaves(DummyHasher, ["a", "b", "c"]);
+        assert_eq!(
+            bytes.root_hex(),
+            MerkleTree::new(DummyHasher, ["a", "b", "c"]).root_hex()
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_with_aad() {
+        let hasher = SHA256Hasher::new();
+        let data = ["a", "b", "c"];
+        let plain = MerkleTreeBuilder::new(hasher.clone())
+            .commit_length(true)
+            .revocable(true)
+            .build(data);
+        let raw_root = plain.root_hex();
+
+        let mut tree = plain.with_aad(&hasher, b"epoch 7");
+        assert_eq!(tree.aad(), Some(&b"epoch 7"[..]));
+        assert_eq!(
+            tree.root_hex(),
+            hasher.hash(&[raw_root.as_bytes(), b"epoch 7"].concat())
+        );
+        assert_eq!(tree.root().hash(), tree.root_hex());
+
+        tree = tree.with_aad(&hasher, b"epoch 8");
+        let mut plain = MerkleTreeBuilder::new(hasher.clone())
+            .commit_length(true)
+            .revocable(true)
+            .build(data);
+        plain.delete_leaf(&hasher, 1).unwrap();
+        tree.delete_leaf(&hasher, 1).unwrap();
+        assert_eq!(
+            tree.root_hex(),
+            hasher.hash(&[plain.root_hex().as_bytes(), b"epoch 8"].concat())
+        );
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_build_trace_replays_to_root() {
+        let hasher = DummyHasher;
+        let data: Vec<String> = (0..5).map(|i| i.to_string()).collect();
+        let tree = MerkleTree::new(hasher.clone(), &data);
+        let trace = tree.build_trace();
+
+        // 5 leaves, then 3 and 2 parents, then the root.
+        assert_eq!(trace.steps().len(), 3 + 2 + 1);
+        assert_eq!(trace.steps()[2].left, trace.steps()[2].right);
+        assert_eq!(trace.steps().last().unwrap().level, tree.height() - 1);
+        assert_eq!(trace.top(), Some(tree.root_hex().as_str()));
+        let leaves: Vec<String> = tree
+            .leaves_ref()
+            .iter()
+            .map(|leaf| leaf.hash().to_string())
+            .collect();
+        assert!(trace.replay(&hasher, &leaves, tree.options()));
+        assert!(!trace.replay(&hasher, &leaves[1..], tree.options()));
+
+        let single = MerkleTree::new(hasher.clone(), ["a"]);
+        assert!(single.build_trace().top().is_none());
+
+        let (sharded, _) = MerkleTree::build_sharded(hasher.clone(), &data, 2);
+        let trace = sharded.build_trace();
+        assert!(trace.replay(&hasher, &leaves, sharded.options()));
+        assert_eq!(trace.steps(), tree.build_trace().steps());
+
+        let options = TreeOptions::new().padding(PaddingStrategy::HashEmpty);
+        let padded = MerkleTreeBuilder::with_options(hasher.clone(), options).build(&data);
+        assert!(
+            padded
+                .build_trace()
+                .replay(&hasher, &leaves, padded.options())
+        );
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_size_ambiguous_options() {
+        assert!(TreeOptions::new().is_size_ambiguous());
+        assert!(TreeOptions::new().bind_index(true).is_size_ambiguous());
+        assert!(!TreeOptions::new().commit_length(true).is_size_ambiguous());
+        assert!(
+            TreeOptions::new()
+                .padding(PaddingStrategy::HashEmpty)
+                .is_size_ambiguous()
+        );
+        assert!(
+            !TreeOptions::new()
+                .padding(PaddingStrategy::HashEmpty)
+                .commit_length(true)
+                .is_size_ambiguous()
+        );
+    }
+
+    #[test]
+    fn test_from_slices() {
+        let hasher = DummyHasher;
+        let buffers = [vec![1u8; 64], vec![2u8; 16], vec![3u8; 32]];
+        let slices: Vec<&[u8]> = buffers.iter().map(Vec::as_slice).collect();
+
+        let tree = MerkleTree::from_slices(hasher.clone(), &slices);
+        assert_eq!(
+            tree.root_hex(),
+            MerkleTree::new(hasher, &buffers).root_hex()
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_entry_bundles_leaf_proof_and_root() {
+        let hasher = SHA256Hasher::new();
+        let data = ["a", "b", "c", "d", "e"];
+        let options = TreeOptions::new().bind_index(true).commit_length(true);
+        let tree = MerkleTreeBuilder::with_options(hasher.clone(), options).build(data);
+        let proofer = DefaultProofer::from_tree(hasher, &tree);
+
+        for (index, item) in data.iter().enumerate() {
+            let entry = tree.entry(index).unwrap();
+            assert_eq!(entry.leaf_index, index);
+            assert_eq!(entry.leaf_hash, tree.leaves_ref()[index].hash());
+            assert_eq!(entry.root, tree.root_hex());
+            assert_eq!(Some(entry.proof.clone()), proofer.generate(index));
+            assert!(proofer.verify(&entry.proof, item, &entry.root));
+        }
+        assert!(tree.entry(5).is_none());
+    }
+
+    #[test]
+    fn test_from_record_buffer() {
+        let hasher = DummyHasher;
+        let buffer: Vec<u8> = (0..5u8).flat_map(|i| [i; 64]).collect();
+
+        let tree = MerkleTree::from_record_buffer(hasher.clone(), &buffer, 64).unwrap();
+        assert_eq!(tree.len(), 5);
+        assert_eq!(
+            tree.root_hex(),
+            MerkleTree::new(hasher.clone(), buffer.chunks(64)).root_hex()
+        );
+
+        for (buffer, record_size) in [(&buffer[..], 60), (&buffer[..], 0), (&[][..], 64)] {
+            assert!(matches!(
+                MerkleTree::from_record_buffer(hasher.clone(), buffer, record_size),
+                Err(MerkleError::InvalidRecordSize { len, record_size: size })
+                    if len == buffer.len() && size == record_size
+            ));
+        }
+    }
+
+    #[test]
+    fn test_from_receiver() {
+        let hasher = DummyHasher;
+        let (tx, rx) = std::sync::mpsc::channel();
+        let producer = std::thread::spawn(move || {
+            for i in 0..10u8 {
+                tx.send(vec![i; 3]).unwrap();
+            }
+        });
+
+        let tree = MerkleTree::from_receiver(hasher.clone(), rx);
+        producer.join().unwrap();
+
+        let expected = MerkleTree::new(hasher, (0..10u8).map(|i| vec![i; 3]));
+        assert_eq!(tree.len(), 10);
+        assert_eq!(tree.root_hex(), expected.root_hex());
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_leaf_salt_modes() {
+        let hasher = SHA256Hasher::new();
+        let data = ["a", "b", "c"];
+        let plain = MerkleTree::new(hasher.clone(), data);
+
+        let salt = [7; 32];
+        let fixed = MerkleTreeBuilder::new(hasher.clone())
+            .leaf_salt_mode(LeafSaltMode::Fixed(salt))
+            .build(data);
+        assert_ne!(fixed.root_hex(), plain.root_hex());
+        assert_eq!(fixed.options().leaf_salt(), Some(salt));
+        assert_eq!(
+            fixed.leaves()[1].hash(),
+            hasher.hash(&[&salt[..], b"b"].concat())
+        );
+
+        let derived = |data: &[&str]| {
+            MerkleTreeBuilder::new(hasher.clone())
+                .bind_index(true)
+                .derive_leaf_salt()
+                .build(data)
+        };
+        let tree = derived(&data);
+        assert_eq!(tree.root_hex(), derived(&data).root_hex());
+        assert_ne!(
+            tree.options().leaf_salt(),
+            derived(&["a", "b"]).options().leaf_salt()
+        );
+
+        let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+        let proof = proofer.generate(2).unwrap();
+        assert!(proofer.verify(&proof, "c", &tree.root_hex()));
+        assert!(!proofer.verify(&proof, "b", &tree.root_hex()));
+
+        // The builder's options do not hold the pending salt, and hashing with them is safe.
+        let builder = MerkleTreeBuilder::new(hasher.clone()).derive_leaf_salt();
+        assert_eq!(builder.options().leaf_salt(), None);
+        assert_eq!(
+            builder.options().leaf_hash(&hasher, 0, b"a"),
+            hasher.hash(b"a")
+        );
+
+        // A salt set afterwards replaces the derived one.
+        let replaced = builder
+            .leaf_salt_mode(LeafSaltMode::Fixed(salt))
+            .build(data);
+        assert_eq!(replaced.root_hex(), fixed.root_hex());
+    }
+
+    #[cfg(all(feature = "sha256", feature = "rand"))]
+    #[test]
+    fn test_random_leaf_salt_is_drawn_once() {
+        let hasher = SHA256Hasher::new();
+        let options = TreeOptions::new().leaf_salt_mode(LeafSaltMode::Random);
+        let salt = options.leaf_salt().unwrap();
+        assert_ne!(
+            salt,
+            TreeOptions::new()
+                .leaf_salt_mode(LeafSaltMode::Random)
+                .leaf_salt()
+                .unwrap()
+        );
+
+        let build = || MerkleTreeBuilder::with_options(hasher.clone(), options).build(["a", "b"]);
+        assert_eq!(build().root_hex(), build().root_hex());
+        assert_eq!(build().options().leaf_salt(), Some(salt));
+    }
+
+    #[test]
+    fn test_builder_parallel_threshold_does_not_change_root() {
+        let data: Vec<Vec<u8>> = (0..100u8).map(|i| vec![i]).collect();
+        let expected = MerkleTree::new(DummyHasher, &data).root_hex();
+
+        for threshold in [0, 2, 64, usize::MAX] {
+            let tree = MerkleTreeBuilder::new(DummyHasher)
+                .parallel_threshold(threshold)
+                .build(&data);
+            assert_eq!(tree.root_hex(), expected);
+            assert_eq!(tree.len(), 100);
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_build_tree_and_proofer_shares_levels() {
+        use crate::proof::Proofer;
+
+        let hasher = SHA256Hasher::new();
+        for count in 1..=9 {
+            let data: Vec<Vec<u8>> = (0..count).map(|i| vec![i as u8]).collect();
+            let (tree, proofer) = build_tree_and_proofer(hasher.clone(), &data);
+            let rebuilt = DefaultProofer::new(hasher.clone(), tree.leaves());
+
+            assert_eq!(proofer.validate(), Ok(()));
+            for (index, item) in data.iter().enumerate() {
+                let proof = proofer.generate(index).unwrap();
+                assert_eq!(proof, rebuilt.generate(index).unwrap());
+                assert_eq!(proof, tree.generate_proof(index).unwrap());
+                assert!(proofer.verify(&proof, item, &tree.root_hex()));
+            }
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_bind_index() {
+        use crate::proof::Proofer;
+
+        let hasher = SHA256Hasher::new();
+        // Identical items make every sibling structure line up.
+        let data = ["same"; 8];
+        let tree = MerkleTreeBuilder::new(hasher.clone())
+            .bind_index(true)
+            .build(data);
+        assert_ne!(
+            tree.root_hex(),
+            MerkleTree::new(hasher.clone(), data).root_hex()
+        );
+        assert_eq!(
+            tree.leaves()[3].hash(),
+            tree.options().leaf_hash(&hasher, 3, b"same")
+        );
+
+        let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+        let proof = proofer.generate(3).unwrap();
+        assert!(proofer.verify(&proof, "same", &tree.root_hex()));
+        assert!(
+            proofer
+                .verify_reader(&proof, "same".as_bytes(), &tree.root_hex())
+                .unwrap()
+        );
+
+        let mut replayed = proof.clone();
+        replayed.leaf_index = 5;
+        assert!(!proofer.verify(&replayed, "same", &tree.root_hex()));
+    }
+
+    #[test]
+    fn test_level_hashes() {
+        let tree = MerkleTree::new(DummyHasher, ["a", "b", "c", "d", "e"]);
+        assert_eq!(tree.height(), 4);
+
+        let sizes: Vec<usize> = (0..tree.height())
+            .map(|level| tree.level_hashes(level).unwrap().len())
+            .collect();
+        assert_eq!(sizes, [6, 4, 2, 1]);
+
+        for level in 1..tree.height() {
+            let below = tree.level_hashes(level - 1).unwrap();
+            let above = tree.level_hashes(level).unwrap();
+            for (i, hash) in above.iter().take(below.len() / 2).enumerate() {
+                assert_eq!(hash, &DummyHasher.combine(&below[2 * i], &below[2 * i + 1]));
+            }
+        }
+
+        assert_eq!(tree.level_hashes(3).unwrap(), [tree.root_hex()]);
+        assert!(tree.level_hashes(4).is_none());
+    }
+
+    #[test]
+    fn test_capacity_and_height_formulas() {
+        assert_eq!(capacity_for_height(0), 0);
+        assert_eq!(capacity_for_height(1), 1);
+        assert_eq!(capacity_for_height(21), 1 << 20);
+        assert_eq!(height_for_leaves(0), 0);
+
+        for count in 1..=33usize {
+            let data: Vec<[u8; 1]> = (0..count).map(|i| [i as u8]).collect();
+            let height = MerkleTree::new(DummyHasher, &data).height();
+
+            assert_eq!(height_for_leaves(count), height);
+            assert!(count <= capacity_for_height(height));
+            assert!(count > capacity_for_height(height - 1));
+        }
+    }
+
+    #[test]
+    fn test_height_counts_levels() {
+        let expected = [
+            (1, 1),
+            (2, 2),
+            (3, 3),
+            (4, 3),
+            (5, 4),
+            (6, 4),
+            (7, 4),
+            (8, 4),
+            (9, 5),
+            (10, 5),
+            (11, 5),
+            (12, 5),
+            (13, 5),
+            (14, 5),
+            (15, 5),
+            (16, 5),
+            (17, 6),
+        ];
+
+        for (count, height) in expected {
+            let data: Vec<[u8; 1]> = (0..count).map(|i| [i as u8]).collect();
+            let tree = MerkleTree::new(DummyHasher, &data);
+            assert_eq!(
+                tree.height(),
+                height,
+                "height of a tree with {count} leaves"
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_lines() {
+        let expected = MerkleTree::new(DummyHasher, ["a", "", "bc"]).root_hex();
+
+        for input in ["a\n\nbc", "a\n\nbc\n", "a\r\n\r\nbc\r\n"] {
+            let tree = MerkleTree::from_lines(DummyHasher, input.as_bytes(), false).unwrap();
+            assert_eq!(tree.root_hex(), expected);
+        }
+
+        let tree = MerkleTree::from_lines(DummyHasher, "a\n\nbc\n".as_bytes(), true).unwrap();
+        assert_eq!(
+            tree.root_hex(),
+            MerkleTree::new(DummyHasher, ["a", "bc"]).root_hex()
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_parallel_build_is_deterministic() {
+        let hasher = SHA256Hasher::new();
+        let data: Vec<[u8; 4]> = (0..5000u32).map(|i| i.to_le_bytes()).collect();
+        let sequential = MerkleTreeBuilder::new(hasher.clone())
+            .parallel_threshold(usize::MAX)
+            .build(&data)
+            .root_hex();
+
+        for _ in 0..20 {
+            let parallel = MerkleTreeBuilder::new(hasher.clone())
+                .parallel_threshold(0)
+                .build(&data);
+            assert_eq!(parallel.root_hex(), sequential);
+        }
+    }
+
+    #[test]
+    fn test_root_from_hashes() {
+        let data = ["a", "b", "c", "d", "e"];
+        let tree = MerkleTree::new(DummyHasher, data);
+        let hashes: Vec<&str> = tree.leaves_ref().iter().map(|leaf| leaf.hash()).collect();
+
+        assert_eq!(
+            root_from_hashes(&DummyHasher, &hashes).unwrap(),
+            tree.root_hex()
+        );
+        assert!(root_from_hashes::<_, String>(&DummyHasher, &[]).is_none());
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_commit_length_disambiguates_padding() {
+        use crate::proof::Proofer;
+
+        let hasher = SHA256Hasher::new();
+        let short = ["a", "b", "c"];
+        let padded = ["a", "b", "c", "c"];
+        assert_eq!(
+            MerkleTree::new(hasher.clone(), short).root_hex(),
+            MerkleTree::new(hasher.clone(), padded).root_hex()
+        );
+
+        let hash_empty = |commit: bool, data: &[&str]| {
+            MerkleTreeBuilder::new(hasher.clone())
+                .padding(PaddingStrategy::HashEmpty)
+                .commit_length(commit)
+                .build(data)
+                .root_hex()
+        };
+        assert_eq!(
+            hash_empty(false, &short),
+            hash_empty(false, &["a", "b", "c", ""])
+        );
+        assert_ne!(
+            hash_empty(true, &short),
+            hash_empty(true, &["a", "b", "c", ""])
+        );
+
+        let builder = || MerkleTreeBuilder::new(hasher.clone()).commit_length(true);
+        let tree = builder().build(short);
+        assert_ne!(tree.root_hex(), builder().build(padded).root_hex());
+        assert_eq!(tree.root().hash(), tree.root_hex());
+
+        let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+        let proof = proofer.generate(2).unwrap();
+        assert!(proofer.verify(&proof, "c", &tree.root_hex()));
+        assert!(proofer.verify_strict(&proof, "c", &tree.root_hex()));
+        assert!(proofer.verify_trace(&proof, "c", &tree.root_hex()).0);
+        assert_eq!(proofer.full_path(2).unwrap().root_hash, tree.root_hex());
+
+        let mut tree = builder().revocable(true).build(short);
+        tree.delete_leaf(&hasher, 0).unwrap();
+        let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+        assert!(proofer.verify(&proofer.generate(2).unwrap(), "c", &tree.root_hex()));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_hash_empty_padding() {
+        use crate::proof::Proofer;
+
+        let hasher = SHA256Hasher::new();
+        let empty = hasher.hash(b"");
+        let options = TreeOptions::new().padding(PaddingStrategy::HashEmpty);
+
+        let tree = MerkleTreeBuilder::with_options(hasher.clone(), options).build(["a", "b", "c"]);
+        let left = hasher.combine(&hasher.hash(b"a"), &hasher.hash(b"b"));
+        let right = hasher.combine(&hasher.hash(b"c"), &empty);
+        assert_eq!(tree.root_hex(), hasher.combine(&left, &right));
+        assert_eq!(tree.level_hashes(0).unwrap()[3], empty);
+        assert_eq!(tree.padded_leaves()[3].hash(), empty);
+        assert!(tree.root().verify_subtree(&hasher));
+
+        let options = options.revocable(true);
+        for count in 1..=9 {
+            let data: Vec<String> = (0..count).map(|i| i.to_string()).collect();
+            let mut tree = MerkleTreeBuilder::with_options(hasher.clone(), options).build(&data);
+            let rebuilt = DefaultProofer::with_options(hasher.clone(), tree.leaves(), &options);
+            let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+            assert!(proofer.validate().is_ok());
+
+            let last = proofer.generate_last().unwrap();
+            assert_eq!(last, rebuilt.generate(count - 1).unwrap());
+            if count > 1 && count % 2 == 1 {
+                assert_eq!(last.path[0].hash, empty);
+            }
+            for (index, item) in data.iter().enumerate() {
+                let proof = tree.generate_proof(index).unwrap();
+                assert_eq!(proof, proofer.generate(index).unwrap());
+                assert!(proofer.verify(&proof, item, &tree.root_hex()));
+            }
+
+            tree.delete_leaf(&hasher, count - 1).unwrap();
+            let mut hashes: Vec<String> = data
+                .iter()
+                .enumerate()
+                .map(|(index, item)| options.leaf_hash(&hasher, index, item.as_bytes()))
+                .collect();
+            hashes[count - 1] = options.tombstone_hash(&hasher, count - 1).unwrap();
+            let expected = MerkleTree::try_build(
+                hasher.clone(),
+                hashes.into_iter().map(Node::new_leaf).collect(),
+                &options,
+                &HashDirOptions::default(),
+            )
+            .unwrap();
+            assert_eq!(tree.root_hex(), expected.root_hex());
+            assert!(tree.root().verify_subtree(&hasher));
+        }
+
+        assert_ne!(
+            tree.root_hex(),
+            MerkleTree::new(hasher.clone(), ["a", "b", "c"]).root_hex()
+        );
+    }
+
+    #[cfg(all(feature = "tar", feature = "sha256"))]
+    #[test]
+    fn test_from_tar_orders_entries_canonically() {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, content) in [("b", "b"), ("a.txt", "a.txt"), ("a/z", "a/z")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_size(0);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "a/", std::io::empty())
+            .unwrap();
+        let archive = builder.into_inner().unwrap();
+
+        let hasher = SHA256Hasher::new();
+        let tree = MerkleTree::from_tar(hasher.clone(), archive.as_slice()).unwrap();
+
+        assert_eq!(tree.paths(), ["a/z", "a.txt", "b"].map(PathBuf::from));
+        assert_eq!(
+            tree.root_hex(),
+            MerkleTree::new(hasher, ["a/z", "a.txt", "b"]).root_hex()
         );
+        assert!(MerkleTree::from_tar(SHA256Hasher::new(), &b"not a tar"[..]).is_err());
+    }
+
+    #[cfg(all(feature = "tar", feature = "sha256"))]
+    #[test]
+    fn test_from_tar_without_files() {
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_size(0);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "a/", std::io::empty())
+            .unwrap();
+        let directory_only = builder.into_inner().unwrap();
+        let empty = tar::Builder::new(Vec::new()).into_inner().unwrap();
+
+        for archive in [directory_only, empty] {
+            assert!(matches!(
+                MerkleTree::from_tar(SHA256Hasher::new(), archive.as_slice()),
+                Err(MerkleError::EmptyInput)
+            ));
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_memory_footprint_grows_with_leaves() {
+        let hasher = SHA256Hasher::new();
+        let small = MerkleTree::new(hasher.clone(), ["a"]);
+        let data: Vec<[u8; 2]> = (0..256u16).map(|i| i.to_le_bytes()).collect();
+        let large = MerkleTree::new(hasher, &data);
+
+        // At least every hash of the 511 nodes, stored in the leaves, the levels and the
+        // boxed subtrees of the root.
+        assert!(large.memory_footprint() > 511 * 64);
+        assert!(small.memory_footprint() < large.memory_footprint());
+        assert!(small.memory_footprint() >= std::mem::size_of::<MerkleTree>());
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_find_by_hash_prefix() {
+        let hasher = SHA256Hasher::new();
+        let tree = MerkleTree::new(hasher.clone(), ["a", "b", "c", "a"]);
+        let hash_of_a = hex::decode(hasher.hash(b"a")).unwrap();
+
+        assert_eq!(tree.find_by_hash_prefix(&hash_of_a[..4]), [0, 3]);
+        assert_eq!(tree.find_by_hash_prefix(&hash_of_a), [0, 3]);
+        assert_eq!(tree.find_by_hash_prefix(&[]), [0, 1, 2, 3]);
+        assert!(tree.find_by_hash_prefix(&[0u8; 33]).is_empty());
     }
 }
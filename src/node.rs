@@ -1,7 +1,9 @@
 //! Contains node definitions for Merkle trees, including leaf and internal node structures.
 
+use crate::hasher::Hasher;
+
 /// Enum representing the type of the node child.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NodeChildType {
     /// Left child
     Left,
@@ -85,4 +87,149 @@ impl Node {
     pub fn status(&self) -> &NodeStatus {
         &self.status
     }
+
+    /// Returns the hashes of both children if the node is internal, without handing out the
+    /// children themselves. Hashes are hex, like every hash of the tree.
+    ///
+    /// # Returns
+    ///
+    /// The left and right child hashes, or `None` for a leaf.
+    pub fn child_hashes(&self) -> Option<(&str, &str)> {
+        match &self.status {
+            NodeStatus::Leaf => None,
+            NodeStatus::Internal(l, r) => Some((l.hash(), r.hash())),
+        }
+    }
+
+    /// Replaces the hash stored at the node.
+    pub(crate) fn set_hash(&mut self, hash: String) {
+        self.hash = hash;
+    }
+
+    /// Returns mutable references to both children if the node is internal.
+    pub(crate) fn children_mut(&mut self) -> Option<(&mut Node, &mut Node)> {
+        match &mut self.status {
+            NodeStatus::Leaf => None,
+            NodeStatus::Internal(l, r) => Some((l, r)),
+        }
+    }
+
+    /// Returns an iterator over this node and all its descendants, in pre-order (a node, then
+    /// its left subtree, then its right subtree).
+    ///
+    /// The traversal keeps its pending nodes on a heap-allocated stack, so arbitrarily deep
+    /// trees do not exhaust the call stack.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { stack: vec![self] }
+    }
+
+    /// Checks that every internal node below and including this one holds the combination of
+    /// its children's hashes. Leaf hashes cannot be checked without their data, so a single
+    /// leaf is always valid.
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - The hasher the tree was built with.
+    ///
+    /// # Returns
+    ///
+    /// `true` if no internal node disagrees with its children.
+    pub fn verify_subtree<H>(&self, hasher: &H) -> bool
+    where
+        H: Hasher,
+    {
+        self.iter().all(|node| match &node.status {
+            NodeStatus::Leaf => true,
+            NodeStatus::Internal(left, right) => {
+                node.hash == hasher.combine(left.hash(), right.hash())
+            }
+        })
+    }
+}
+
+/// Pre-order iterator over a node and its descendants, created by [`Node::iter`].
+pub struct Iter<'a> {
+    stack: Vec<&'a Node>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if let NodeStatus::Internal(left, right) = &node.status {
+            self.stack.push(right);
+            self.stack.push(left);
+        }
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hasher::DummyHasher;
+
+    /// Builds a degenerate tree where every internal node has a leaf on its right, `depth`
+    /// levels deep.
+    fn degenerate(hasher: &DummyHasher, depth: usize) -> Node {
+        let mut node = Node::new_leaf(hasher.hash(b"leaf"));
+        for i in 0..depth {
+            let sibling = Node::new_leaf(hasher.hash(&i.to_le_bytes()));
+            let hash = hasher.combine(node.hash(), sibling.hash());
+            node = Node::new_internal(hash, node, sibling);
+        }
+        node
+    }
+
+    #[test]
+    fn test_iter_is_pre_order() {
+        let hasher = DummyHasher;
+        let root = degenerate(&hasher, 2);
+        let left = root.status().left().unwrap();
+
+        let order: Vec<&str> = root.iter().map(Node::hash).collect();
+        assert_eq!(
+            order,
+            [
+                root.hash(),
+                left.hash(),
+                left.status().left().unwrap().hash(),
+                left.status().right().unwrap().hash(),
+                root.status().right().unwrap().hash(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deep_traversal_does_not_overflow() {
+        let hasher = DummyHasher;
+        let mut root = degenerate(&hasher, 1000);
+
+        assert_eq!(root.iter().count(), 2001);
+        assert!(root.verify_subtree(&hasher));
+
+        let mut node = &mut root;
+        while node.status().left().is_some() {
+            node = node.children_mut().unwrap().0;
+        }
+        node.set_hash(hasher.hash(b"tampered"));
+        assert!(!root.verify_subtree(&hasher));
+    }
+
+    #[test]
+    fn test_child_hashes() {
+        let hasher = DummyHasher;
+        let root = degenerate(&hasher, 1);
+        let (left, right) = root.child_hashes().unwrap();
+        assert_eq!(left, root.status().left().unwrap().hash());
+        assert_eq!(right, root.status().right().unwrap().hash());
+        assert_eq!(hasher.combine(left, right), root.hash());
+
+        assert!(
+            Node::new_leaf(hasher.hash(b"leaf"))
+                .child_hashes()
+                .is_none()
+        );
+    }
 }
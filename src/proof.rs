@@ -1,13 +1,19 @@
 //! Merkle tree proof and verification implementation
 
 use crate::{
-    hasher::Hasher,
+    error::{MerkleError, ParseError, ValidationError},
+    hasher::{self, HashAlgorithm, Hasher},
+    incremental::Frontier,
+    merkletree::{self, MerkleTree, TreeOptions},
     node::{Node, NodeChildType},
 };
 use rayon::prelude::*;
 
+/// Default number of proofs verified by each Rayon task in `DefaultProofer::verify_batch`.
+pub const DEFAULT_VERIFY_CHUNK_SIZE: usize = 256;
+
 /// Represents a single step in a Merkle proof path.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ProofNode {
     /// The hash value of the sibling node.
     pub hash: String,
@@ -15,13 +21,558 @@ pub struct ProofNode {
     pub child_type: NodeChildType,
 }
 
+#[cfg(feature = "generic-array")]
+impl<N> From<(digest::generic_array::GenericArray<u8, N>, NodeChildType)> for ProofNode
+where
+    N: digest::generic_array::ArrayLength<u8>,
+{
+    /// Creates a step from a sibling digest returned by a RustCrypto hasher and its side.
+    fn from(
+        (hash, child_type): (digest::generic_array::GenericArray<u8, N>, NodeChildType),
+    ) -> Self {
+        Self {
+            hash: hex::encode(hash),
+            child_type,
+        }
+    }
+}
+
+impl From<([u8; 32], NodeChildType)> for ProofNode {
+    /// Creates a step from a raw 32-byte sibling digest and its side.
+    fn from((hash, child_type): ([u8; 32], NodeChildType)) -> Self {
+        Self {
+            hash: hex::encode(hash),
+            child_type,
+        }
+    }
+}
+
 /// A Merkle proof containing the path from a leaf to the root.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MerkleProof {
     /// The sequence of sibling hashes needed to reconstruct the path to root.
     pub path: Vec<ProofNode>,
     /// The index of the leaf node this proof corresponds.
     pub leaf_index: usize,
+    /// The hash of the leaf, if carried by the proof. Proofs generated by the proofers always
+    /// include it; it is only needed by `verify_standalone`.
+    pub leaf_hash: Option<String>,
+}
+
+impl MerkleProof {
+    /// Creates a proof from raw parts, e.g. a proof imported from another system.
+    ///
+    /// # Arguments
+    ///
+    /// * `leaf_index` - The index of the proven leaf.
+    /// * `path` - The raw sibling digests from the leaf level up, each with `true` when the
+    ///   sibling is the left child.
+    ///
+    /// The proof carries no leaf hash, so it is verified against the leaf data or hash.
+    pub fn new<T>(leaf_index: usize, path: Vec<(T, bool)>) -> Self
+    where
+        T: AsRef<[u8]>,
+    {
+        let path = path
+            .into_iter()
+            .map(|(hash, is_left)| ProofNode {
+                hash: hex::encode(hash),
+                child_type: if is_left {
+                    NodeChildType::Left
+                } else {
+                    NodeChildType::Right
+                },
+            })
+            .collect();
+
+        Self {
+            path,
+            leaf_index,
+            leaf_hash: None,
+        }
+    }
+
+    /// Creates a proof from a Bitcoin-style merkle branch.
+    ///
+    /// # Arguments
+    ///
+    /// * `hashes` - The sibling hashes from the lowest level up, in internal byte order.
+    /// * `position` - The index of the leaf, whose bit `i` is set when the node at level `i`
+    ///   is a right child, i.e. when the sibling is on the left.
+    pub fn from_bitcoin_branch(hashes: &[[u8; 32]], position: u32) -> Self {
+        let path = hashes
+            .iter()
+            .enumerate()
+            .map(|(level, hash)| ProofNode {
+                hash: hex::encode(hash),
+                child_type: if position.checked_shr(level as u32).unwrap_or(0) & 1 == 1 {
+                    NodeChildType::Left
+                } else {
+                    NodeChildType::Right
+                },
+            })
+            .collect();
+
+        Self {
+            path,
+            leaf_index: position as usize,
+            leaf_hash: None,
+        }
+    }
+
+    /// Converts the proof to a Bitcoin-style merkle branch, the inverse of
+    /// `from_bitcoin_branch`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a sibling hash is not a hex-encoded 32-byte digest, or if the path directions
+    /// do not fit in a `u32` position.
+    pub fn to_bitcoin_branch(&self) -> (Vec<[u8; 32]>, u32) {
+        let hashes = self
+            .path
+            .iter()
+            .map(|node| {
+                let hash = hex::decode(&node.hash).expect("sibling hash is not hex-encoded");
+                hash.try_into().expect("sibling hash is not 32 bytes long")
+            })
+            .collect();
+        let position = u32::try_from(self.implied_index()).expect("position does not fit a u32");
+
+        (hashes, position)
+    }
+
+    /// Encodes the proof in a self-describing binary format: the leaf index as a big-endian
+    /// `u64`, the number of steps as a big-endian `u32`, then for each step a direction byte
+    /// (`0` when the sibling is on the left, `1` on the right), the hash length as one byte and
+    /// the raw hash. The leaf hash is not included.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a sibling hash is not hex-encoded or longer than 255 bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(self.leaf_index as u64).to_be_bytes());
+        bytes.extend_from_slice(&(self.path.len() as u32).to_be_bytes());
+
+        for node in &self.path {
+            let hash = hex::decode(&node.hash).expect("sibling hash is not hex-encoded");
+            bytes.push(match node.child_type {
+                NodeChildType::Left => 0,
+                NodeChildType::Right => 1,
+            });
+            bytes.push(u8::try_from(hash.len()).expect("sibling hash is too long"));
+            bytes.extend(hash);
+        }
+
+        bytes
+    }
+
+    /// Decodes a proof encoded by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], ParseError> {
+            if bytes.len() < len {
+                return Err(ParseError::Truncated);
+            }
+            let (head, tail) = bytes.split_at(len);
+            *bytes = tail;
+            Ok(head)
+        }
+
+        let mut bytes = bytes;
+        let leaf_index = u64::from_be_bytes(take(&mut bytes, 8)?.try_into().unwrap());
+        let steps = u32::from_be_bytes(take(&mut bytes, 4)?.try_into().unwrap());
+
+        let mut path = Vec::new();
+        for _ in 0..steps {
+            let child_type = match take(&mut bytes, 1)?[0] {
+                0 => NodeChildType::Left,
+                1 => NodeChildType::Right,
+                byte => return Err(ParseError::InvalidDirection(byte)),
+            };
+            let len = take(&mut bytes, 1)?[0] as usize;
+            let hash = hex::encode(take(&mut bytes, len)?);
+            path.push(ProofNode { hash, child_type });
+        }
+
+        if !bytes.is_empty() {
+            return Err(ParseError::TrailingBytes);
+        }
+
+        Ok(Self {
+            path,
+            leaf_index: leaf_index as usize,
+            leaf_hash: None,
+        })
+    }
+
+    /// Encodes the path in a fixed layout of exactly `path.len() * (1 + L)` bytes, where `L`
+    /// is the length of the sibling digests: for each step a direction byte (`0` when the
+    /// sibling is on the left, `1` on the right) then the raw sibling hash. The leaf index,
+    /// the leaf hash and `L` are not included.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a sibling hash is not hex-encoded, or if the sibling hashes do not all have
+    /// the same length.
+    pub fn to_flat_bytes(&self) -> Vec<u8> {
+        let hash_len = self.path.first().map_or(0, |node| node.hash.len() / 2);
+        let mut bytes = Vec::with_capacity(self.path.len() * (1 + hash_len));
+
+        for node in &self.path {
+            let hash = hex::decode(&node.hash).expect("sibling hash is not hex-encoded");
+            assert_eq!(hash.len(), hash_len, "sibling hashes differ in length");
+            bytes.push(match node.child_type {
+                NodeChildType::Left => 0,
+                NodeChildType::Right => 1,
+            });
+            bytes.extend(hash);
+        }
+
+        bytes
+    }
+
+    /// Decodes a path of 32-byte digests encoded by `to_flat_bytes`, for the leaf at
+    /// `leaf_index` conveyed separately.
+    ///
+    /// # Returns
+    ///
+    /// The proof, `ParseError::Truncated` if the length is not a multiple of 33, or
+    /// `ParseError::InvalidDirection` for a direction byte other than `0` or `1`.
+    pub fn from_flat_bytes(bytes: &[u8], leaf_index: usize) -> Result<Self, ParseError> {
+        Self::from_flat_bytes_with_len(bytes, leaf_index, 32)
+    }
+
+    /// Decodes a path encoded by `to_flat_bytes` whose sibling digests are `hash_len` bytes
+    /// long, e.g. `hasher.output_len()`, for the leaf at `leaf_index` conveyed separately.
+    ///
+    /// # Returns
+    ///
+    /// The proof, `ParseError::Truncated` if the length is not a multiple of `1 + hash_len`,
+    /// or `ParseError::InvalidDirection` for a direction byte other than `0` or `1`.
+    pub fn from_flat_bytes_with_len(
+        bytes: &[u8],
+        leaf_index: usize,
+        hash_len: usize,
+    ) -> Result<Self, ParseError> {
+        let step_len = 1 + hash_len;
+        if !bytes.len().is_multiple_of(step_len) {
+            return Err(ParseError::Truncated);
+        }
+
+        let path = bytes
+            .chunks(step_len)
+            .map(|step| {
+                let child_type = match step[0] {
+                    0 => NodeChildType::Left,
+                    1 => NodeChildType::Right,
+                    byte => return Err(ParseError::InvalidDirection(byte)),
+                };
+                Ok(ProofNode {
+                    hash: hex::encode(&step[1..]),
+                    child_type,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            path,
+            leaf_index,
+            leaf_hash: None,
+        })
+    }
+
+    /// Encodes the proof like `to_bytes`, as a lowercase hex string.
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_bytes())
+    }
+
+    /// Decodes a proof encoded by `to_hex`.
+    pub fn from_hex(proof_hex: &str) -> Result<Self, ParseError> {
+        Self::from_bytes(&hex::decode(proof_hex).map_err(|_| ParseError::InvalidHex)?)
+    }
+
+    /// Cheaply checks the structure of the proof, before any hashing, to reject garbage early.
+    ///
+    /// A well-formed proof has at most one step per bit of a `usize`, a `leaf_index` matching
+    /// its path directions, and sibling hashes that are non-empty and all of the same length.
+    ///
+    /// # Arguments
+    ///
+    /// * `expected_height` - If known, the height of the tree, which must then be
+    ///   `path.len() + 1`.
+    pub fn is_well_formed(&self, expected_height: Option<usize>) -> bool {
+        if self.path.len() > usize::BITS as usize {
+            return false;
+        }
+        if expected_height.is_some_and(|height| height != self.path.len() + 1) {
+            return false;
+        }
+        if self.implied_index() != self.leaf_index {
+            return false;
+        }
+
+        let hash_len = self.path.first().map_or(0, |node| node.hash.len());
+        self.path
+            .iter()
+            .all(|node| !node.hash.is_empty() && node.hash.len() == hash_len)
+    }
+
+    /// Cheaply checks that the proof could belong to a tree of `leaf_count` leaves, before any
+    /// hashing, to reject proofs replayed from a tree of another size or truncated.
+    ///
+    /// Odd levels are padded up to an even length, so a tree of `leaf_count` leaves has
+    /// `ceil(log2(leaf_count))` levels above the leaves and every proof has that many steps.
+    ///
+    /// # Returns
+    ///
+    /// `true` if `leaf_index < leaf_count` and the path has the expected length, `false`
+    /// otherwise, including for a `leaf_count` above the largest power of two of a `usize`.
+    pub fn is_plausible_for_size(&self, leaf_count: usize) -> bool {
+        if self.leaf_index >= leaf_count {
+            return false;
+        }
+
+        match leaf_count.checked_next_power_of_two() {
+            Some(width) => self.path.len() == width.trailing_zeros() as usize,
+            None => false,
+        }
+    }
+
+    /// Returns the leaf index implied by the path directions: the bit of each level is set
+    /// when the sibling is on the left, i.e. when the node is a right child.
+    ///
+    /// For a well-formed proof this equals `leaf_index`. Directions past the width of a
+    /// `usize` cannot belong to any index and are ignored.
+    pub fn implied_index(&self) -> usize {
+        self.path
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.child_type == NodeChildType::Left)
+            .map(|(level, _)| 1usize.checked_shl(level as u32).unwrap_or(0))
+            .fold(0, |index, bit| index | bit)
+    }
+
+    /// Compares this proof with `other`, typically a freshly generated proof for the same
+    /// leaf, to find where a failing proof goes wrong.
+    ///
+    /// # Returns
+    ///
+    /// The levels, `0` being the leaves, at which the sibling hashes or directions disagree, in
+    /// increasing order. If one path is longer, every level past the end of the shorter one
+    /// disagrees.
+    pub fn diff(&self, other: &MerkleProof) -> Vec<usize> {
+        let levels = self.path.len().max(other.path.len());
+
+        (0..levels)
+            .filter(|&level| self.path.get(level) != other.path.get(level))
+            .collect()
+    }
+
+    /// Patches the proof after a node of the tree changed, if that node is one of its siblings.
+    ///
+    /// After a leaf update, call it once per level with the new hash of each ancestor of the
+    /// updated leaf to keep a cached proof fresh without regenerating it. Changes to the
+    /// proof's own leaf or its ancestors are not siblings, and require a new proof. This
+    /// includes the last node of an odd level, which is paired with its own padding duplicate:
+    /// the proof cannot tell that duplicate from a regular sibling.
+    ///
+    /// # Arguments
+    ///
+    /// * `changed_level` - The level of the changed node, `0` being the leaves.
+    /// * `changed_index` - The position of the changed node within its level.
+    /// * `new_hash` - The new hash of the changed node.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a sibling hash was updated.
+    pub fn apply_update(
+        &mut self,
+        changed_level: usize,
+        changed_index: usize,
+        new_hash: &str,
+    ) -> bool {
+        let Some(sibling) = self.path.get_mut(changed_level) else {
+            return false;
+        };
+        if (self.leaf_index >> changed_level) ^ 1 != changed_index {
+            return false;
+        }
+
+        sibling.hash = new_hash.to_string();
+        true
+    }
+
+    /// Returns the authentication path as raw bytes, with no framing, for fixed-layout records.
+    ///
+    /// # Returns
+    ///
+    /// The direction bits, one per step packed least significant bit first, set when the
+    /// sibling is on the left; and the concatenated raw sibling hashes. The leaf index and leaf
+    /// hash are not included.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a sibling hash is not hex-encoded.
+    pub fn path_bytes(&self) -> (Vec<u8>, Vec<u8>) {
+        let mut directions = vec![0u8; self.path.len().div_ceil(8)];
+        let mut hashes = Vec::new();
+
+        for (i, node) in self.path.iter().enumerate() {
+            if node.child_type == NodeChildType::Left {
+                directions[i / 8] |= 1 << (i % 8);
+            }
+            hashes.extend(hex::decode(&node.hash).expect("sibling hash is not hex-encoded"));
+        }
+
+        (directions, hashes)
+    }
+
+    /// Rebuilds a proof from the output of `path_bytes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `directions` - The packed direction bits.
+    /// * `hashes` - The concatenated raw sibling hashes.
+    /// * `hash_len` - The length in bytes of each hash, e.g. 32.
+    /// * `leaf_index` - The index of the leaf, stored separately by the caller.
+    ///
+    /// # Returns
+    ///
+    /// `None` if `hashes` is not a whole number of hashes, or `directions` is too short for
+    /// them.
+    pub fn from_path_bytes(
+        directions: &[u8],
+        hashes: &[u8],
+        hash_len: usize,
+        leaf_index: usize,
+    ) -> Option<Self> {
+        if hash_len == 0 || !hashes.len().is_multiple_of(hash_len) {
+            return None;
+        }
+        let steps = hashes.len() / hash_len;
+        if directions.len() < steps.div_ceil(8) {
+            return None;
+        }
+
+        let path = hashes
+            .chunks(hash_len)
+            .enumerate()
+            .map(|(i, hash)| ProofNode {
+                hash: hex::encode(hash),
+                child_type: if directions[i / 8] & (1 << (i % 8)) != 0 {
+                    NodeChildType::Left
+                } else {
+                    NodeChildType::Right
+                },
+            })
+            .collect();
+
+        Some(Self {
+            path,
+            leaf_index,
+            leaf_hash: None,
+        })
+    }
+
+    /// Verifies the proof without the leaf data, starting from the embedded `leaf_hash`.
+    ///
+    /// This is meant for verifiers that trust the leaf hash but cannot see the data, because it
+    /// is large or private.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the proof carries no leaf hash or if the reconstructed root differs from
+    /// `root_hash`.
+    pub fn verify_standalone<H>(&self, hasher: &H, root_hash: &str) -> bool
+    where
+        H: Hasher,
+    {
+        match &self.leaf_hash {
+            Some(hash) => fold_path(hasher, self, hash.clone()) == root_hash,
+            None => false,
+        }
+    }
+}
+
+/// The complete authentication path of a leaf, meant for display, logging or audit rather
+/// than transport.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FullPath {
+    /// The hash of the leaf.
+    pub leaf_hash: String,
+    /// The siblings from the leaf level up, with their direction.
+    pub path: Vec<ProofNode>,
+    /// The hashes of the leaf's ancestors strictly between the leaf and the root.
+    pub intermediate_hashes: Vec<String>,
+    /// The root hash.
+    pub root_hash: String,
+}
+
+/// The metadata a standalone verifier needs to reject structurally impossible proofs before
+/// any hashing, as returned by `DefaultProofer::proof_context`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofContext {
+    /// The height of the tree, counted in levels: proofs have `height - 1` steps.
+    pub height: usize,
+    /// The number of leaves of the tree.
+    pub leaf_count: usize,
+    /// The name of the hasher, see `Hasher::algorithm_name`.
+    pub hasher_name: String,
+}
+
+impl ProofContext {
+    /// Returns `true` if `proof` could belong to the tree: it is well-formed with the expected
+    /// number of steps, and its leaf index is in bounds. The proof is not verified.
+    pub fn accepts(&self, proof: &MerkleProof) -> bool {
+        proof.leaf_index < self.leaf_count && proof.is_well_formed(Some(self.height))
+    }
+}
+
+/// A proof for a verifier that already holds every leaf hash, such as a synced replica of a
+/// transparency log: it carries neither the data nor the leaf hash, only the path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexProof {
+    /// The index of the proven leaf, whose hash the verifier looks up on its side.
+    pub leaf_index: usize,
+    /// The sequence of sibling hashes needed to reconstruct the path to root.
+    pub path: Vec<ProofNode>,
+}
+
+impl From<IndexProof> for MerkleProof {
+    fn from(proof: IndexProof) -> Self {
+        Self {
+            path: proof.path,
+            leaf_index: proof.leaf_index,
+            leaf_hash: None,
+        }
+    }
+}
+
+/// A proof that a tree of `old_size` leaves grew into a larger one by appending leaves, and
+/// nothing else.
+///
+/// It carries the roots of the complete subtrees of the old tree, which stay untouched by
+/// appends: they rebuild the old root, then the new root once the appended leaves are added.
+/// Without `TreeOptions::commit_length`, a root duplicating its last leaf is ambiguous about
+/// its size, so `old_size` is only bound to the old root when the length is committed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppendProof {
+    /// The number of leaves of the old tree.
+    pub old_size: usize,
+    /// The roots of the complete subtrees of the old tree, one per set bit of `old_size`,
+    /// lowest level first.
+    pub frontier: Vec<String>,
+}
+
+/// A proof that a subset of the leaves, held by the verifier, belongs to a tree: the sibling
+/// hashes the subset cannot compute by itself, each shared path being sent once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubsetProof {
+    /// The number of leaves of the tree, which determines its shape.
+    pub leaf_count: usize,
+    /// The missing hashes, level by level from the leaves up, by increasing position within
+    /// each level.
+    pub siblings: Vec<String>,
 }
 
 pub trait Proofer {
@@ -47,14 +598,188 @@ pub trait Proofer {
     /// # Returns
     ///
     /// `true` if the proof is valid and the data exists in the tree, `false` otherwise.
+    fn verify_bytes(&self, proof: &MerkleProof, data: &[u8], root_hash: &str) -> bool;
+
+    /// Verifies that a piece of data exists in the tree using a Merkle proof.
+    ///
+    /// This is a convenience wrapper around `verify_bytes` accepting anything that can be
+    /// viewed as bytes. Being generic, it is not available on `dyn Proofer`.
     fn verify<T>(&self, proof: &MerkleProof, data: T, root_hash: &str) -> bool
     where
-        T: AsRef<[u8]>;
+        T: AsRef<[u8]>,
+        Self: Sized,
+    {
+        self.verify_bytes(proof, data.as_ref(), root_hash)
+    }
+}
+
+/// Builds the proof for the leaf at `index` from unpadded tree levels.
+///
+/// `hash_of` extracts the hash of a level element, so the same logic serves proofers storing
+/// full nodes and proofers storing hashes only. The last node of an odd level is paired with
+/// `padding_hash` if given, or with itself otherwise.
+fn generate_path<T>(
+    levels: &[Vec<T>],
+    index: usize,
+    padding_hash: Option<&str>,
+    hash_of: impl Fn(&T) -> &str,
+) -> Option<MerkleProof> {
+    if index >= levels[0].len() {
+        return None;
+    }
+
+    let mut path = Vec::new();
+    let mut current_index = index;
+
+    for level in &levels[..levels.len() - 1] {
+        // The sibling is found by flipping the last bit. Only the last node of an odd level
+        // has none: it is a left child, paired with its padding.
+        let sibling = match level.get(current_index ^ 1) {
+            Some(node) => hash_of(node),
+            None => padding_hash.unwrap_or_else(|| hash_of(&level[current_index])),
+        };
+
+        let child_type = if current_index % 2 == 1 {
+            NodeChildType::Left
+        } else {
+            NodeChildType::Right
+        };
+
+        path.push(ProofNode {
+            hash: sibling.to_string(),
+            child_type,
+        });
+
+        current_index >>= 1;
+    }
+
+    Some(MerkleProof {
+        path,
+        leaf_index: index,
+        leaf_hash: Some(hash_of(&levels[0][index]).to_string()),
+    })
+}
+
+/// Walks up the tree from a leaf hash using the proof path and returns the computed root.
+pub(crate) fn fold_path<H: Hasher>(hasher: &H, proof: &MerkleProof, hash: String) -> String {
+    let mut current_hash = hash;
+    // Walk up the tree using the proof path
+    for proof_node in &proof.path {
+        current_hash = match proof_node.child_type {
+            NodeChildType::Left => hasher.combine(&proof_node.hash, &current_hash),
+            NodeChildType::Right => hasher.combine(&current_hash, &proof_node.hash),
+        };
+    }
+
+    current_hash
+}
+
+/// Quotes `value` as a JSON string, escaping it. Hashes are usually hex, but custom hashers
+/// and expected roots received from clients can hold anything.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Verifies hex-encoded data at the string boundary of an application, e.g. a REST handler.
+///
+/// # Arguments
+///
+/// * `proof_hex` - The proof, as encoded by `MerkleProof::to_hex`.
+/// * `data` - The original data to verify.
+/// * `root_hex` - The expected root hash, in hex of any case.
+/// * `algo` - The algorithm the tree was built with.
+///
+/// # Returns
+///
+/// Whether the proof is valid, or the reason why the proof or root cannot be parsed.
+pub fn verify_hex(
+    proof_hex: &str,
+    data: &[u8],
+    root_hex: &str,
+    algo: HashAlgorithm,
+) -> Result<bool, ParseError> {
+    let proof = MerkleProof::from_hex(proof_hex)?;
+    let root = hex::decode(root_hex).map_err(|_| ParseError::InvalidHex)?;
+
+    Ok(fold_path(&algo, &proof, algo.hash(data)) == hex::encode(root))
+}
+
+/// Verifies a data file against a root stored in a trusted file, as a command-line integrity
+/// check would. The data file is streamed through the hasher, never held in memory.
+///
+/// The root file holds either the raw digest, or its hex encoding in any case, optionally
+/// surrounded by whitespace such as a trailing newline; the format is detected from the
+/// length, which is known from `algo`.
+///
+/// # Arguments
+///
+/// * `proof` - The proof of the data.
+/// * `data_path` - The file holding the original data of the leaf.
+/// * `root_path` - The trusted file holding the expected root.
+/// * `algo` - The algorithm the tree was built with.
+///
+/// # Returns
+///
+/// Whether the proof is valid, `MerkleError::Io` if a file cannot be read, or
+/// `MerkleError::InvalidRootFile` if the root file does not hold a digest.
+pub fn verify_against_root_file<P, Q>(
+    proof: &MerkleProof,
+    data_path: P,
+    root_path: Q,
+    algo: HashAlgorithm,
+) -> Result<bool, MerkleError>
+where
+    P: AsRef<std::path::Path>,
+    Q: AsRef<std::path::Path>,
+{
+    let io_error = |path: &std::path::Path| {
+        let path = path.to_path_buf();
+        move |source| MerkleError::Io { path, source }
+    };
+    let (data_path, root_path) = (data_path.as_ref(), root_path.as_ref());
+
+    let content = std::fs::read(root_path).map_err(io_error(root_path))?;
+    let digest_len = algo.output_len();
+    let root = if content.len() == digest_len {
+        hex::encode(content)
+    } else {
+        match std::str::from_utf8(&content).map(str::trim) {
+            Ok(text) if text.len() == 2 * digest_len => hex::decode(text).map(hex::encode).ok(),
+            _ => None,
+        }
+        .ok_or_else(|| MerkleError::InvalidRootFile {
+            path: root_path.to_path_buf(),
+        })?
+    };
+
+    let file = std::fs::File::open(data_path).map_err(io_error(data_path))?;
+    let leaf_hash = hasher::hash_reader(&algo, file).map_err(io_error(data_path))?;
+
+    Ok(fold_path(&algo, proof, leaf_hash) == root)
 }
 
+/// The default proofer, storing every level of the tree.
+///
+/// Leaves are verified through the same hasher used to build the tree, so any leaf
+/// transformation configured on it (salting, separate leaf and node functions, ...) is applied
+/// by `verify` exactly as it was during construction.
 pub struct DefaultProofer<H: Hasher> {
     hasher: H,
     levels: Vec<Vec<Node>>,
+    options: TreeOptions,
+    /// Constant hash odd levels are padded with, if not duplicating their last node.
+    padding_hash: Option<String>,
 }
 
 impl<H> DefaultProofer<H>
@@ -62,97 +787,735 @@ where
     H: Hasher,
 {
     pub fn new(hasher: H, leaves: Vec<Node>) -> Self {
+        Self::with_options(hasher, leaves, &TreeOptions::default())
+    }
+
+    /// Creates a proofer building its levels according to `options`, such as the ones of the
+    /// `MerkleTreeBuilder` used for the tree.
+    pub fn with_options(hasher: H, leaves: Vec<Node>, options: &TreeOptions) -> Self {
+        let padding_hash = options.padding_hash(&hasher);
         let mut levels = Vec::new();
         levels.push(leaves.clone());
 
         let mut current_level = leaves;
         while current_level.len() > 1 {
-            if current_level.len() % 2 != 0 {
-                current_level.push(current_level.last().unwrap().clone());
+            if !current_level.len().is_multiple_of(2) {
+                let pad = match &padding_hash {
+                    Some(hash) => Node::new_leaf(hash.clone()),
+                    None => current_level.last().unwrap().clone(),
+                };
+                current_level.push(pad);
             }
-            let next_level: Vec<Node> = current_level
-                .par_chunks(2)
-                .map(|pair| {
-                    let (left, right) = (&pair[0], &pair[1]);
-                    let combined = [left.hash().as_bytes(), right.hash().as_bytes()].concat();
-                    let hash = hasher.hash(&combined);
-                    Node::new_internal(hash, left.clone(), right.clone())
-                })
-                .collect();
+            let next_level: Vec<Node> = options.combine_pairs(&current_level, |left, right| {
+                let hash = hasher.combine(left.hash(), right.hash());
+                Node::new_internal(hash, left.clone(), right.clone())
+            });
 
             levels.push(next_level.clone());
             current_level = next_level;
         }
 
-        Self { hasher, levels }
+        Self {
+            hasher,
+            levels,
+            options: *options,
+            padding_hash,
+        }
     }
 
-    pub fn verify_hash(&self, proof: &MerkleProof, hash: String, root_hash: &str) -> bool {
-        let mut current_hash = hash;
-        // Walk up the tree using the proof path
-        for proof_node in &proof.path {
-            let combined: String = match proof_node.child_type {
-                NodeChildType::Left => format!("{}{}", proof_node.hash, current_hash),
-                NodeChildType::Right => format!("{}{}", current_hash, proof_node.hash),
-            };
-            current_hash = self.hasher.hash(combined.as_bytes());
-        }
+    /// Creates a proofer from leaf hashes persisted earlier, without the original data.
+    ///
+    /// Levels are built with the same padding and combine order as from the data, so the
+    /// proofs are identical.
+    pub fn from_leaf_hashes<I, S>(hasher: H, hashes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let leaves = hashes
+            .into_iter()
+            .map(|hash| Node::new_leaf(hash.into()))
+            .collect();
 
-        // Check if the computed root matches the expected root
-        current_hash == root_hash
+        Self::new(hasher, leaves)
     }
-}
 
-impl<H> Proofer for DefaultProofer<H>
-where
-    H: Hasher,
-{
-    fn generate(&self, index: usize) -> Option<MerkleProof> {
-        if index >= self.levels[0].len() {
-            return None;
+    /// Creates a proofer for `tree` from the levels it already computed, without hashing.
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - The hasher the tree was built with.
+    /// * `tree` - The tree to generate proofs for.
+    pub fn from_tree(hasher: H, tree: &MerkleTree) -> Self {
+        Self {
+            padding_hash: tree.options().padding_hash(&hasher),
+            hasher,
+            levels: tree.node_levels(),
+            options: *tree.options(),
         }
+    }
 
-        let mut path = Vec::new();
-        let mut current_index = index;
-
-        for level in &self.levels[..self.levels.len() - 1] {
-            // Flip the last bit and ensures that it never goes out-of-bounds
-            let sibling_index = (current_index ^ 1).min(level.len() - 1);
+    /// Creates a proofer from levels stored elsewhere, such as deserialized from untrusted
+    /// storage, after checking them with `validate`.
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - The hasher the levels were built with.
+    /// * `levels` - Every level of the tree, from the leaves up to the root, without padding.
+    pub fn from_levels(hasher: H, levels: Vec<Vec<Node>>) -> Result<Self, ValidationError> {
+        let proofer = Self {
+            hasher,
+            levels,
+            options: TreeOptions::default(),
+            padding_hash: None,
+        };
+        proofer.validate()?;
+        Ok(proofer)
+    }
 
-            let sibling = &level[sibling_index];
+    /// Checks the structural invariants of the levels: each level has half the nodes of the
+    /// one below (rounded up), the top one is the root, and every internal node's hash is the
+    /// combination of its children's hashes.
+    ///
+    /// # Returns
+    ///
+    /// The first violation found, if any.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.levels.first().is_none_or(|leaves| leaves.is_empty()) {
+            return Err(ValidationError::Empty);
+        }
+        if self.levels.last().unwrap().len() != 1 {
+            return Err(ValidationError::MissingRoot);
+        }
 
-            let child_type = if sibling_index < current_index {
-                NodeChildType::Left
+        for (level, pair) in self.levels.windows(2).enumerate() {
+            let (below, above) = (&pair[0], &pair[1]);
+            // Nothing may sit above the root.
+            let expected = if below.len() == 1 {
+                0
             } else {
-                NodeChildType::Right
+                below.len().div_ceil(2)
             };
+            if above.len() != expected {
+                return Err(ValidationError::LevelSize {
+                    level: level + 1,
+                    expected,
+                    actual: above.len(),
+                });
+            }
 
-            path.push(ProofNode {
-                hash: sibling.hash().to_string(),
-                child_type,
-            });
+            for (index, node) in above.iter().enumerate() {
+                let left = below[2 * index].hash();
+                let right = match below.get(2 * index + 1) {
+                    Some(right) => right.hash(),
+                    None => self.padding_hash.as_deref().unwrap_or(left),
+                };
+                if node.hash() != self.hasher.combine(left, right) {
+                    return Err(ValidationError::HashMismatch {
+                        level: level + 1,
+                        index,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn verify_hash(&self, proof: &MerkleProof, hash: String, root_hash: &str) -> bool {
+        // Check if the computed root matches the expected root
+        self.computed_root(proof, hash) == root_hash
+    }
+
+    /// Verifies a proof like `verify`, for a tree whose root was bound to `aad` with
+    /// `MerkleTree::with_aad`. The proof only verifies under the same context.
+    pub fn verify_with_aad<T>(
+        &self,
+        proof: &MerkleProof,
+        data: T,
+        root_hash: &str,
+        aad: &[u8],
+    ) -> bool
+    where
+        T: AsRef<[u8]>,
+    {
+        let computed = self.computed_root(proof, self.leaf_hash(proof, data.as_ref()));
+        merkletree::aad_root(&self.hasher, &computed, aad) == root_hash
+    }
+
+    /// Verifies many proofs against the same root in parallel, with chunks of
+    /// `DEFAULT_VERIFY_CHUNK_SIZE` proofs per task.
+    ///
+    /// # Returns
+    ///
+    /// The result of `verify` for each item, in order.
+    pub fn verify_batch<T>(&self, items: &[(MerkleProof, T)], root_hash: &str) -> Vec<bool>
+    where
+        T: AsRef<[u8]> + Sync,
+    {
+        self.verify_batch_chunked(items, root_hash, DEFAULT_VERIFY_CHUNK_SIZE)
+    }
+
+    /// Verifies many proofs against the same root in parallel, like `verify_batch`.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The proofs, each with the original data of its leaf.
+    /// * `root_hash` - The expected root hash of the tree.
+    /// * `chunk_size` - The number of proofs verified by each Rayon task, so that small proofs
+    ///   are not split into more tasks than they are worth. Zero is treated as one.
+    ///
+    /// # Returns
+    ///
+    /// The result of `verify` for each item, in order.
+    pub fn verify_batch_chunked<T>(
+        &self,
+        items: &[(MerkleProof, T)],
+        root_hash: &str,
+        chunk_size: usize,
+    ) -> Vec<bool>
+    where
+        T: AsRef<[u8]> + Sync,
+    {
+        items
+            .par_chunks(chunk_size.max(1))
+            .flat_map_iter(|chunk| {
+                chunk
+                    .iter()
+                    .map(|(proof, data)| self.verify_bytes(proof, data.as_ref(), root_hash))
+            })
+            .collect()
+    }
+
+    /// Returns the complete authentication path of the leaf at `index`, read from the stored
+    /// levels.
+    ///
+    /// # Returns
+    ///
+    /// The path, or `None` if `index` is out of bounds.
+    pub fn full_path(&self, index: usize) -> Option<FullPath> {
+        let proof = self.generate(index)?;
+        let top = self.levels.len() - 1;
+
+        Some(FullPath {
+            leaf_hash: self.levels[0][index].hash().to_string(),
+            path: proof.path,
+            intermediate_hashes: (1..top)
+                .map(|level| self.levels[level][index >> level].hash().to_string())
+                .collect(),
+            root_hash: self.options.root_hash(
+                &self.hasher,
+                self.levels[top][0].hash(),
+                self.levels[0].len(),
+            ),
+        })
+    }
+
+    /// Verifies a proof like `verify`, also returning every hash reconstructed on the way up.
+    ///
+    /// # Returns
+    ///
+    /// Whether the proof is valid, and the hashes computed at each level: the leaf hash of
+    /// `data` first, then one per proof step, the last one being the computed root. Comparing
+    /// them with the expected nodes shows where an invalid proof diverges.
+    pub fn verify_trace<T>(
+        &self,
+        proof: &MerkleProof,
+        data: T,
+        root_hash: &str,
+    ) -> (bool, Vec<String>)
+    where
+        T: AsRef<[u8]>,
+    {
+        let mut trace = Vec::with_capacity(proof.path.len() + 1);
+        trace.push(self.leaf_hash(proof, data.as_ref()));
+
+        for proof_node in &proof.path {
+            let current_hash = trace.last().unwrap();
+            let next = match proof_node.child_type {
+                NodeChildType::Left => self.hasher.combine(&proof_node.hash, current_hash),
+                NodeChildType::Right => self.hasher.combine(current_hash, &proof_node.hash),
+            };
+            trace.push(next);
+        }
 
-            current_index >>= 1;
+        let top = trace.last().unwrap();
+        let root = self
+            .options
+            .root_hash(&self.hasher, top, self.levels[0].len());
+        if &root != top {
+            trace.push(root);
         }
 
-        Some(MerkleProof {
-            path,
-            leaf_index: index,
+        (trace.last().unwrap() == root_hash, trace)
+    }
+
+    /// Verifies a proof like `verify_trace`, returning the trace as a JSON document to send to
+    /// a client, who can diff it against their own computation to find where it diverges.
+    ///
+    /// The document has the form:
+    ///
+    /// ```json
+    /// {"leaf_index": 0, "leaf_hash": "..",
+    ///  "steps": [{"sibling": "..", "direction": "right", "hash": ".."}],
+    ///  "computed_root": "..", "expected_root": "..", "valid": false}
+    /// ```
+    ///
+    /// where `direction` is the side of the sibling and `hash` the one reconstructed at that
+    /// level. `computed_root` differs from the last `hash` only if the root commits to the
+    /// leaf count.
+    pub fn verify_report_json<T>(&self, proof: &MerkleProof, data: T, root_hash: &str) -> String
+    where
+        T: AsRef<[u8]>,
+    {
+        let (valid, trace) = self.verify_trace(proof, data, root_hash);
+
+        let steps: Vec<String> = proof
+            .path
+            .iter()
+            .zip(&trace[1..])
+            .map(|(node, hash)| {
+                let direction = match node.child_type {
+                    NodeChildType::Left => "left",
+                    NodeChildType::Right => "right",
+                };
+                format!(
+                    r#"{{"sibling":{},"direction":"{direction}","hash":{}}}"#,
+                    json_string(&node.hash),
+                    json_string(hash)
+                )
+            })
+            .collect();
+
+        format!(
+            r#"{{"leaf_index":{},"leaf_hash":{},"steps":[{}],"computed_root":{},"expected_root":{},"valid":{valid}}}"#,
+            proof.leaf_index,
+            json_string(&trace[0]),
+            steps.join(","),
+            json_string(trace.last().unwrap()),
+            json_string(root_hash)
+        )
+    }
+
+    /// Verifies a proof for data read from `reader`, streaming it through the hasher instead of
+    /// holding it in memory.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(true)` if the proof is valid for the streamed data, `Ok(false)` if it is not, or the
+    /// error returned by `reader`.
+    pub fn verify_reader<R>(
+        &self,
+        proof: &MerkleProof,
+        reader: R,
+        root_hash: &str,
+    ) -> std::io::Result<bool>
+    where
+        R: std::io::Read,
+    {
+        let prefix = self.options.leaf_prefix(proof.leaf_index);
+        let hash = hasher::hash_reader(
+            &self.hasher,
+            std::io::Read::chain(prefix.as_slice(), reader),
+        )?;
+
+        Ok(self.verify_hash(proof, hash, root_hash))
+    }
+
+    /// Returns the metadata of the tree a verifier needs to validate the structure of proofs:
+    /// the height, the number of leaves and the name of the hasher.
+    pub fn proof_context(&self) -> ProofContext {
+        ProofContext {
+            height: self.levels.len(),
+            leaf_count: self.levels[0].len(),
+            hasher_name: self.hasher.algorithm_name(),
+        }
+    }
+
+    /// Returns `true` if the leaves of the proofer produce exactly `root`, the published root
+    /// mixed with the leaf count if it is committed.
+    ///
+    /// This is the ground truth for a verifier holding the full data: lightweight checks such
+    /// as `MerkleProof::is_plausible_for_size` only rule out impossible claims about a root,
+    /// this one confirms it. No hashing is needed, the root is read from the stored levels.
+    pub fn root_matches(&self, root: &str) -> bool {
+        let top = self.levels.last().expect("proofer has at least one level")[0].hash();
+        self.options
+            .root_hash(&self.hasher, top, self.levels[0].len())
+            == root
+    }
+
+    /// Generates a proof for the leaf at `index` carrying only the path, for verifiers that
+    /// already hold the leaf hashes.
+    ///
+    /// # Returns
+    ///
+    /// The proof, or `None` if `index` is out of bounds.
+    pub fn generate_index_proof(&self, index: usize) -> Option<IndexProof> {
+        let proof = self.generate(index)?;
+
+        Some(IndexProof {
+            leaf_index: proof.leaf_index,
+            path: proof.path,
         })
     }
 
-    fn verify<T>(&self, proof: &MerkleProof, data: T, root_hash: &str) -> bool
+    /// Generates a proof that the tree is the one of its first `old_size` leaves with the
+    /// remaining leaves appended, in O(log n) hashes whatever the number of appended leaves.
+    ///
+    /// # Returns
+    ///
+    /// The proof, or `None` if `old_size` is zero or larger than the tree.
+    pub fn append_proof(&self, old_size: usize) -> Option<AppendProof> {
+        if old_size == 0 || old_size > self.levels[0].len() {
+            return None;
+        }
+
+        let frontier = (0..usize::BITS - old_size.leading_zeros())
+            .filter(|level| old_size >> level & 1 == 1)
+            .map(|level| {
+                let index = (old_size >> level) - 1;
+                self.levels[level as usize][index].hash().to_string()
+            })
+            .collect();
+
+        Some(AppendProof { old_size, frontier })
+    }
+
+    /// Verifies that the tree of `new_root` is the tree of `old_root` with `new_leaves`
+    /// appended, in order.
+    ///
+    /// Only the hasher and options of the proofer are used, so a verifier holding the old root
+    /// needs neither tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `old_root` - The root the verifier trusts.
+    /// * `new_root` - The claimed root after the append.
+    /// * `new_leaves` - The original data of the appended leaves.
+    /// * `proof` - The proof returned by `append_proof`.
+    pub fn verify_append<T>(
+        &self,
+        old_root: &str,
+        new_root: &str,
+        new_leaves: &[T],
+        proof: &AppendProof,
+    ) -> bool
+    where
+        T: AsRef<[u8]>,
+    {
+        let Some(mut frontier) = Frontier::from_roots(proof.old_size, &proof.frontier) else {
+            return false;
+        };
+        let root = |frontier: &Frontier| {
+            frontier.root(&self.hasher, &self.options, self.padding_hash.as_deref())
+        };
+
+        if root(&frontier).as_deref() != Some(old_root) {
+            return false;
+        }
+
+        for (offset, data) in new_leaves.iter().enumerate() {
+            let index = proof.old_size + offset;
+            let hash = self.options.leaf_hash(&self.hasher, index, data.as_ref());
+            frontier.push(&self.hasher, hash);
+        }
+
+        root(&frontier).as_deref() == Some(new_root)
+    }
+
+    /// Generates a proof for the leaves at `indices`, to be verified with `verify_subset` by a
+    /// verifier holding their hashes. Duplicate indices are ignored.
+    ///
+    /// # Returns
+    ///
+    /// The proof, or `None` if `indices` is empty or holds an index out of bounds.
+    pub fn subset_proof(&self, indices: &[usize]) -> Option<SubsetProof> {
+        let leaf_count = self.levels[0].len();
+        if indices.is_empty() || indices.iter().any(|&index| index >= leaf_count) {
+            return None;
+        }
+
+        let mut known = indices.to_vec();
+        known.sort_unstable();
+        known.dedup();
+
+        let mut siblings = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            for (i, &position) in known.iter().enumerate() {
+                let sibling = position ^ 1;
+                let sibling_known = if position % 2 == 0 {
+                    known.get(i + 1) == Some(&sibling)
+                } else {
+                    i > 0 && known[i - 1] == sibling
+                };
+                // A missing right sibling is padding, which the verifier derives by itself.
+                if !sibling_known && sibling < level.len() {
+                    siblings.push(level[sibling].hash().to_string());
+                }
+            }
+            known = known.iter().map(|position| position >> 1).collect();
+            known.dedup();
+        }
+
+        Some(SubsetProof {
+            leaf_count,
+            siblings,
+        })
+    }
+
+    /// Verifies that the leaves a verifier holds belong to the tree of `root_hash`.
+    ///
+    /// # Arguments
+    ///
+    /// * `held` - The index and hash of each held leaf, in any order, as proven by `proof`.
+    /// * `proof` - The proof returned by `subset_proof` for the same indices.
+    /// * `root_hash` - The expected root hash of the tree.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the held leaves and the proof lead to `root_hash` and every sibling of the
+    /// proof is used, `false` otherwise.
+    pub fn verify_subset(
+        &self,
+        held: &[(usize, String)],
+        proof: &SubsetProof,
+        root_hash: &str,
+    ) -> bool {
+        let mut current: Vec<(usize, String)> = held.to_vec();
+        current.sort_unstable_by_key(|(index, _)| *index);
+        current.dedup();
+        let in_bounds = current
+            .last()
+            .is_some_and(|(index, _)| *index < proof.leaf_count);
+        if !in_bounds || current.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            return false;
+        }
+
+        let mut siblings = proof.siblings.iter();
+        let mut level_len = proof.leaf_count;
+        while level_len > 1 {
+            let mut next = Vec::with_capacity(current.len());
+            let mut nodes = current.into_iter().peekable();
+            while let Some((position, hash)) = nodes.next() {
+                let parent = if position % 2 == 1 {
+                    let Some(left) = siblings.next() else {
+                        return false;
+                    };
+                    self.hasher.combine(left, &hash)
+                } else if nodes.peek().is_some_and(|(next, _)| *next == position + 1) {
+                    let (_, right) = nodes.next().unwrap();
+                    self.hasher.combine(&hash, &right)
+                } else if position + 1 < level_len {
+                    let Some(right) = siblings.next() else {
+                        return false;
+                    };
+                    self.hasher.combine(&hash, right)
+                } else {
+                    let padding = self.padding_hash.as_deref().unwrap_or(&hash);
+                    self.hasher.combine(&hash, padding)
+                };
+                next.push((position >> 1, parent));
+            }
+            current = next;
+            level_len = level_len.div_ceil(2);
+        }
+
+        siblings.next().is_none()
+            && self
+                .options
+                .root_hash(&self.hasher, &current[0].1, proof.leaf_count)
+                == root_hash
+    }
+
+    /// Verifies an index proof against the leaf hash the verifier holds at its index.
+    ///
+    /// The path directions must match `proof.leaf_index`, so a proof for another position
+    /// cannot be passed off with the hash looked up at this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The proof returned by `generate_index_proof`.
+    /// * `leaf_hash` - The verifier's own hash of the leaf at `proof.leaf_index`.
+    /// * `root_hash` - The expected root hash of the tree.
+    pub fn verify_index_proof(&self, proof: &IndexProof, leaf_hash: &str, root_hash: &str) -> bool {
+        let proof = MerkleProof::from(proof.clone());
+
+        proof.implied_index() == proof.leaf_index
+            && self.verify_hash(&proof, leaf_hash.to_string(), root_hash)
+    }
+
+    /// Verifies a proof like `verify`, also requiring its `leaf_index` to match the index
+    /// implied by the path directions, which detects tampered indices.
+    pub fn verify_strict<T>(&self, proof: &MerkleProof, data: T, root_hash: &str) -> bool
+    where
+        T: AsRef<[u8]>,
+    {
+        proof.implied_index() == proof.leaf_index
+            && self.verify_bytes(proof, data.as_ref(), root_hash)
+    }
+
+    /// Generates a proof for `data` without knowing its position, by looking up the first leaf
+    /// holding it. The lookup scans the leaves, hashing `data` once, or once per leaf when
+    /// leaves are bound to their index.
+    ///
+    /// # Returns
+    ///
+    /// The proof, or `None` if `data` is not a leaf of the tree.
+    pub fn prove_data(&self, data: &[u8]) -> Option<MerkleProof> {
+        let leaves = &self.levels[0];
+        let index = if self.options.binds_index() {
+            (0..leaves.len())
+                .find(|&i| leaves[i].hash() == self.options.leaf_hash(&self.hasher, i, data))
+        } else {
+            let hash = self.options.leaf_hash(&self.hasher, 0, data);
+            leaves.iter().position(|leaf| leaf.hash() == hash)
+        }?;
+
+        self.generate(index)
+    }
+
+    /// Generates a Merkle proof for the last leaf of the tree.
+    ///
+    /// When the number of leaves is odd, the last leaf is paired with its padding, so its first
+    /// sibling is itself, or the padding hash with `PaddingStrategy::HashEmpty`.
+    pub fn generate_last(&self) -> Option<MerkleProof> {
+        let last = self.levels[0].len().checked_sub(1)?;
+
+        self.generate(last)
+    }
+
+    /// Returns `true` if `expected` is the hash of the leaf holding `data` at position `index`,
+    /// including any leaf transformation applied by this proofer's hasher and its options
+    /// (salt, index binding).
+    pub fn is_leaf_hash_of(&self, index: usize, data: &[u8], expected: &str) -> bool {
+        self.options.leaf_hash(&self.hasher, index, data) == expected
+    }
+
+    /// Verifies a proof against a root embedded as raw digest bytes inside a larger blob.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The Merkle proof.
+    /// * `data` - The original data to verify.
+    /// * `blob` - The structure carrying the root, e.g. a signed header.
+    /// * `root_offset` - The offset of the first root byte inside `blob`.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the root read from `blob` matches the one computed from the proof, `false` if
+    /// it does not or if the root would not fit inside `blob` at `root_offset`.
+    pub fn verify_embedded_root<T>(
+        &self,
+        proof: &MerkleProof,
+        data: T,
+        blob: &[u8],
+        root_offset: usize,
+    ) -> bool
     where
         T: AsRef<[u8]>,
     {
+        let computed = self.computed_root(proof, self.leaf_hash(proof, data.as_ref()));
+        let root_len = computed.len() / 2;
+
+        match root_offset
+            .checked_add(root_len)
+            .and_then(|end| blob.get(root_offset..end))
+        {
+            Some(root) => hex::encode(root) == computed,
+            None => false,
+        }
+    }
+
+    /// Root reconstructed from `leaf_hash` along `proof`, including the final leaf count mix
+    /// if the length is committed.
+    fn computed_root(&self, proof: &MerkleProof, leaf_hash: String) -> String {
+        let top = fold_path(&self.hasher, proof, leaf_hash);
+        self.options
+            .root_hash(&self.hasher, &top, self.levels[0].len())
+    }
+
+    /// Hash of the leaf holding `data` at the position of `proof`.
+    fn leaf_hash(&self, proof: &MerkleProof, data: &[u8]) -> String {
+        self.options.leaf_hash(&self.hasher, proof.leaf_index, data)
+    }
+}
+
+impl<H> Proofer for DefaultProofer<H>
+where
+    H: Hasher,
+{
+    fn generate(&self, index: usize) -> Option<MerkleProof> {
+        generate_path(&self.levels, index, self.padding_hash.as_deref(), |node| {
+            node.hash()
+        })
+    }
+
+    fn verify_bytes(&self, proof: &MerkleProof, data: &[u8], root_hash: &str) -> bool {
         // Start with the hash of the data
-        let hash: String = self.hasher.hash(data.as_ref());
+        let hash: String = self.leaf_hash(proof, data);
+        self.verify_hash(proof, hash, root_hash)
+    }
+}
+
+/// A proofer for trees built with a `CommutativeHasher`, whose proofs verify regardless of
+/// their direction flags.
+pub type CommutativeProofer<H> = DefaultProofer<hasher::CommutativeHasher<H>>;
+
+/// A proofer that only stores the hashes of each level.
+///
+/// Unlike [`DefaultProofer`], no `Node` (and thus no boxed subtree) is kept or cloned, which
+/// makes it much lighter for large trees. The generated proofs are identical.
+pub struct HashOnlyProofer<H: Hasher> {
+    hasher: H,
+    levels: Vec<Vec<String>>,
+}
+
+impl<H> HashOnlyProofer<H>
+where
+    H: Hasher,
+{
+    pub fn new(hasher: H, leaves: Vec<Node>) -> Self {
+        let mut current_level: Vec<String> =
+            leaves.iter().map(|leaf| leaf.hash().to_string()).collect();
+        let mut levels = Vec::new();
+
+        while current_level.len() > 1 {
+            levels.push(current_level.clone());
+            if !current_level.len().is_multiple_of(2) {
+                current_level.push(current_level.last().unwrap().clone());
+            }
+            current_level = TreeOptions::default()
+                .combine_pairs(&current_level, |left, right| hasher.combine(left, right));
+        }
+        levels.push(current_level);
+
+        Self { hasher, levels }
+    }
+
+    pub fn verify_hash(&self, proof: &MerkleProof, hash: String, root_hash: &str) -> bool {
+        fold_path(&self.hasher, proof, hash) == root_hash
+    }
+}
+
+impl<H> Proofer for HashOnlyProofer<H>
+where
+    H: Hasher,
+{
+    fn generate(&self, index: usize) -> Option<MerkleProof> {
+        generate_path(&self.levels, index, None, |hash| hash.as_str())
+    }
+
+    fn verify_bytes(&self, proof: &MerkleProof, data: &[u8], root_hash: &str) -> bool {
+        let hash: String = self.hasher.hash(data);
         self.verify_hash(proof, hash, root_hash)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "sha256")]
+    use crate::merkletree::{LeafSaltMode, MerkleTreeBuilder, PaddingStrategy};
     use crate::{hasher::*, merkletree::MerkleTree};
 
     use super::*;
@@ -171,6 +1534,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "sha256")]
     #[test]
     fn test_proof_generation_and_verification_sha256() {
         let hasher = SHA256Hasher::new();
@@ -185,6 +1549,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "sha256")]
     #[test]
     fn test_proof_not_valid() {
         let hasher = SHA256Hasher::new();
@@ -201,4 +1566,1017 @@ mod tests {
 
         assert!(!proofer.verify(&proof, b"e", tree.root().hash()));
     }
+
+    #[cfg(all(feature = "blake3", feature = "sha256"))]
+    #[test]
+    fn test_proof_with_dual_hasher() {
+        let hasher = DualHasher::new(SHA256Hasher::new(), Blake3Hasher::new());
+        let data = vec!["a", "b", "c"];
+        let tree = MerkleTree::new(hasher.clone(), data.clone());
+        let proofer = DefaultProofer::new(hasher, tree.leaves());
+
+        let ab = Blake3Hasher.combine(&SHA256Hasher.hash(b"a"), &SHA256Hasher.hash(b"b"));
+        let cc = Blake3Hasher.combine(&SHA256Hasher.hash(b"c"), &SHA256Hasher.hash(b"c"));
+        assert_eq!(tree.root().hash(), Blake3Hasher.combine(&ab, &cc));
+
+        for (index, item) in data.iter().enumerate() {
+            let proof = proofer.generate(index).unwrap();
+
+            assert!(proofer.verify(&proof, item, tree.root().hash()));
+        }
+
+        let single = DefaultProofer::new(SHA256Hasher::new(), tree.leaves());
+        let proof = single.generate(0).unwrap();
+        assert!(!single.verify(&proof, "a", tree.root().hash()));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_verify_embedded_root() {
+        let hasher = SHA256Hasher::new();
+        let data = vec!["a", "b", "c", "d"];
+        let tree = MerkleTree::new(hasher.clone(), data.clone());
+        let proofer = DefaultProofer::new(hasher, tree.leaves());
+        let proof = proofer.generate(2).unwrap();
+
+        let mut blob = vec![1u8, 0, 0, 0, 42, 42, 42, 42];
+        blob.extend(hex::decode(tree.root().hash()).unwrap());
+        blob.extend([0xff; 4]);
+
+        assert!(proofer.verify_embedded_root(&proof, "c", &blob, 8));
+        assert!(!proofer.verify_embedded_root(&proof, "c", &blob, 7));
+        assert!(!proofer.verify_embedded_root(&proof, "a", &blob, 8));
+        assert!(!proofer.verify_embedded_root(&proof, "c", &blob, 12));
+        assert!(!proofer.verify_embedded_root(&proof, "c", &blob, usize::MAX));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_hash_only_proofer_matches_default_proofer() {
+        let hasher = SHA256Hasher::new();
+        for count in 1..=9 {
+            let data: Vec<Vec<u8>> = (0..count).map(|i| vec![i as u8]).collect();
+            let tree = MerkleTree::new(hasher.clone(), &data);
+            let proofer = DefaultProofer::new(hasher.clone(), tree.leaves());
+            let lean = HashOnlyProofer::new(hasher.clone(), tree.leaves());
+
+            for (index, item) in data.iter().enumerate() {
+                let proof = lean.generate(index).unwrap();
+
+                assert_eq!(proof, proofer.generate(index).unwrap());
+                assert!(lean.verify(&proof, item, tree.root().hash()));
+            }
+            assert!(lean.generate(count).is_none());
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_proof_with_salted_hasher() {
+        let hasher = SaltedHasher::new(SHA256Hasher::new(), b"tree-salt".to_vec());
+        let data = vec!["a", "b", "c", "d", "e"];
+        let tree = MerkleTree::new(hasher.clone(), data.clone());
+        let proofer = DefaultProofer::new(hasher, tree.leaves());
+        let unconfigured = DefaultProofer::new(SHA256Hasher::new(), tree.leaves());
+
+        for (index, item) in data.iter().enumerate() {
+            let proof = proofer.generate(index).unwrap();
+
+            assert!(proofer.verify(&proof, item, tree.root().hash()));
+            assert!(!unconfigured.verify(&proof, item, tree.root().hash()));
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_is_leaf_hash_of() {
+        let hasher = SaltedHasher::new(SHA256Hasher::new(), b"salt".to_vec());
+        let tree = MerkleTree::new(hasher.clone(), ["a", "b"]);
+        let proofer = DefaultProofer::new(hasher, tree.leaves());
+
+        assert!(proofer.is_leaf_hash_of(0, b"a", tree.leaves()[0].hash()));
+        assert!(!proofer.is_leaf_hash_of(0, b"a", tree.leaves()[1].hash()));
+        assert!(!proofer.is_leaf_hash_of(0, b"a", &SHA256Hasher.hash(b"a")));
+
+        let options = TreeOptions::new()
+            .bind_index(true)
+            .leaf_salt_mode(LeafSaltMode::Fixed([3; 32]));
+        let tree = MerkleTreeBuilder::with_options(SHA256Hasher, options).build(["a", "b"]);
+        let proofer = DefaultProofer::from_tree(SHA256Hasher, &tree);
+        assert!(proofer.is_leaf_hash_of(1, b"b", tree.leaves()[1].hash()));
+        assert!(!proofer.is_leaf_hash_of(0, b"b", tree.leaves()[1].hash()));
+        assert!(!proofer.is_leaf_hash_of(1, b"b", &SHA256Hasher.hash(b"b")));
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_verify_reader() {
+        let hasher = Blake3Hasher::new();
+        let files = [
+            std::fs::read("tests/pics/cubbit.png.enc.0").unwrap(),
+            std::fs::read("tests/pics/cubbit.png.enc.1").unwrap(),
+        ];
+        let tree = MerkleTree::new(hasher.clone(), &files);
+        let proofer = DefaultProofer::new(hasher, tree.leaves());
+        let proof = proofer.generate(1).unwrap();
+
+        let file = std::fs::File::open("tests/pics/cubbit.png.enc.1").unwrap();
+        assert!(
+            proofer
+                .verify_reader(&proof, file, tree.root().hash())
+                .unwrap()
+        );
+
+        let file = std::fs::File::open("tests/pics/cubbit.png.enc.0").unwrap();
+        assert!(
+            !proofer
+                .verify_reader(&proof, file, tree.root().hash())
+                .unwrap()
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_proofer_as_trait_object() {
+        let hasher = SHA256Hasher::new();
+        let data = vec!["a", "b", "c"];
+        let tree = MerkleTree::new(hasher.clone(), data.clone());
+        let proofers: Vec<Box<dyn Proofer>> = vec![
+            Box::new(DefaultProofer::new(hasher.clone(), tree.leaves())),
+            Box::new(HashOnlyProofer::new(hasher, tree.leaves())),
+        ];
+
+        for proofer in &proofers {
+            let proof = proofer.generate(1).unwrap();
+
+            assert!(proofer.verify_bytes(&proof, b"b", tree.root().hash()));
+            assert!(!proofer.verify_bytes(&proof, b"a", tree.root().hash()));
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_generate_last() {
+        let hasher = SHA256Hasher::new();
+        for count in 1..=9 {
+            let data: Vec<Vec<u8>> = (0..count).map(|i| vec![i as u8]).collect();
+            let tree = MerkleTree::new(hasher.clone(), &data);
+            let proofer = DefaultProofer::new(hasher.clone(), tree.leaves());
+
+            let proof = proofer.generate_last().unwrap();
+
+            assert_eq!(proof.leaf_index, count - 1);
+            assert_eq!(
+                tree.last_leaf().unwrap().hash(),
+                hasher.hash(&data[count - 1])
+            );
+            assert!(proofer.verify(&proof, &data[count - 1], tree.root().hash()));
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_every_leaf_of_odd_trees_verifies() {
+        let hasher = SHA256Hasher::new();
+        for count in [3, 5, 7] {
+            let data: Vec<String> = (0..count).map(|i| i.to_string()).collect();
+            for padding in [PaddingStrategy::Duplicate, PaddingStrategy::HashEmpty] {
+                let builder = MerkleTreeBuilder::new(hasher.clone()).padding(padding);
+                let options = *builder.options();
+                let tree = builder.build(&data);
+                let root = tree.root_hex();
+                let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+                let hash_only = HashOnlyProofer::new(hasher.clone(), tree.leaves());
+
+                for (index, item) in data.iter().enumerate() {
+                    let proof = proofer.generate(index).unwrap();
+                    assert_eq!(tree.generate_proof(index), Some(proof.clone()));
+                    assert!(proof.is_well_formed(Some(tree.height())));
+                    assert!(proofer.verify(&proof, item, &root));
+                    for other in data.iter().filter(|other| *other != item) {
+                        assert!(!proofer.verify(&proof, other, &root));
+                    }
+                    if options.padding_hash(&hasher).is_none() {
+                        assert_eq!(hash_only.generate(index), Some(proof));
+                    }
+                }
+
+                // The last leaf is a left child paired with its padding at the first level.
+                let last = proofer.generate(count - 1).unwrap();
+                assert_eq!(last.path[0].child_type, NodeChildType::Right);
+                let padding = match padding {
+                    PaddingStrategy::Duplicate => hasher.hash(data[count - 1].as_bytes()),
+                    PaddingStrategy::HashEmpty => hasher.hash(b""),
+                };
+                assert_eq!(last.path[0].hash, padding);
+            }
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_verify_with_aad() {
+        let hasher = SHA256Hasher::new();
+        let tree = MerkleTree::new(hasher.clone(), ["a", "b", "c"]);
+        let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+        let root = tree.with_aad(&hasher, b"policy-1").root_hex();
+
+        let proof = proofer.generate(1).unwrap();
+        assert!(proofer.verify_with_aad(&proof, "b", &root, b"policy-1"));
+        assert!(!proofer.verify_with_aad(&proof, "b", &root, b"policy-2"));
+        assert!(!proofer.verify_with_aad(&proof, "a", &root, b"policy-1"));
+        assert!(!proofer.verify(&proof, "b", &root));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_subset_proof() {
+        let hasher = SHA256Hasher::new();
+        let data: Vec<String> = (0..11).map(|i| i.to_string()).collect();
+        let options = TreeOptions::new()
+            .commit_length(true)
+            .padding(PaddingStrategy::HashEmpty);
+
+        for options in [TreeOptions::default(), options] {
+            let tree = MerkleTreeBuilder::with_options(hasher.clone(), options).build(&data);
+            let root = tree.root_hex();
+            let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+            let held = |indices: &[usize]| -> Vec<(usize, String)> {
+                indices
+                    .iter()
+                    .map(|&i| (i, tree.leaves_ref()[i].hash().to_string()))
+                    .collect()
+            };
+
+            for indices in [
+                &[3][..],
+                &[0, 1],
+                &[10],
+                &[9, 2, 4, 10],
+                &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10],
+            ] {
+                let proof = proofer.subset_proof(indices).unwrap();
+                assert!(proofer.verify_subset(&held(indices), &proof, &root));
+            }
+
+            // Siblings shared by the paths are only sent once.
+            let single = proofer.subset_proof(&[0]).unwrap();
+            assert_eq!(single.siblings.len(), tree.height() - 1);
+            assert_eq!(
+                proofer.subset_proof(&[0, 1]).unwrap().siblings.len(),
+                tree.height() - 2
+            );
+            assert_eq!(proofer.subset_proof(&[0; 11]), Some(single.clone()));
+            assert!(
+                proofer
+                    .subset_proof(&(0..11).collect::<Vec<_>>())
+                    .unwrap()
+                    .siblings
+                    .is_empty()
+            );
+
+            let proof = proofer.subset_proof(&[2, 5]).unwrap();
+            let mut forged = held(&[2, 5]);
+            forged[1].1 = hasher.hash(b"forged");
+            assert!(!proofer.verify_subset(&forged, &proof, &root));
+            assert!(!proofer.verify_subset(&held(&[2]), &proof, &root));
+            assert!(!proofer.verify_subset(&held(&[2, 4]), &proof, &root));
+            assert!(!proofer.verify_subset(&[], &proof, &root));
+
+            assert!(proofer.subset_proof(&[]).is_none());
+            assert!(proofer.subset_proof(&[11]).is_none());
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_root_matches() {
+        let hasher = SHA256Hasher::new();
+        let data = ["a", "b", "c"];
+        for commit_length in [false, true] {
+            let tree = MerkleTreeBuilder::new(hasher.clone())
+                .commit_length(commit_length)
+                .build(data);
+            let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+
+            assert!(proofer.root_matches(&tree.root_hex()));
+            assert!(!proofer.root_matches(&MerkleTree::new(hasher.clone(), ["a", "b"]).root_hex()));
+        }
+
+        // Duplicate padding makes these datasets share a root, unless the length is committed.
+        let proofer = DefaultProofer::new(
+            hasher.clone(),
+            MerkleTree::new(hasher.clone(), data).leaves(),
+        );
+        let padded = MerkleTree::new(hasher.clone(), ["a", "b", "c", "c"]);
+        assert!(proofer.root_matches(&padded.root_hex()));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_append_proof() {
+        let hasher = SHA256Hasher::new();
+        let data: Vec<String> = (0..13).map(|i| i.to_string()).collect();
+        let options = TreeOptions::new()
+            .bind_index(true)
+            .commit_length(true)
+            .padding(PaddingStrategy::HashEmpty);
+
+        for options in [TreeOptions::default(), options] {
+            let build = |data: &[String]| {
+                MerkleTreeBuilder::with_options(hasher.clone(), options).build(data)
+            };
+            let tree = build(&data);
+            let new_root = tree.root_hex();
+            let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+
+            for old_size in 1..=data.len() {
+                let old_root = build(&data[..old_size]).root_hex();
+                let new_leaves = &data[old_size..];
+                let proof = proofer.append_proof(old_size).unwrap();
+                assert_eq!(proof.frontier.len(), old_size.count_ones() as usize);
+                assert!(proofer.verify_append(&old_root, &new_root, new_leaves, &proof));
+
+                if let Some((_, rest)) = new_leaves.split_first() {
+                    let mut tampered = new_leaves.to_vec();
+                    tampered[0] = "tampered".to_string();
+                    assert!(!proofer.verify_append(&old_root, &new_root, &tampered, &proof));
+                    assert!(!proofer.verify_append(&old_root, &new_root, rest, &proof));
+                    assert!(!proofer.verify_append(&new_root, &old_root, new_leaves, &proof));
+                }
+            }
+
+            assert!(proofer.append_proof(0).is_none());
+            assert!(proofer.append_proof(14).is_none());
+
+            let mut proof = proofer.append_proof(5).unwrap();
+            proof.frontier.pop();
+            let old_root = build(&data[..5]).root_hex();
+            assert!(!proofer.verify_append(&old_root, &new_root, &data[5..], &proof));
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_verify_trace() {
+        let hasher = SHA256Hasher::new();
+        let data = ["a", "b", "c", "d", "e"];
+        let tree = MerkleTree::new(hasher.clone(), data);
+        let proofer = DefaultProofer::new(hasher.clone(), tree.leaves());
+        let root = tree.root_hex();
+
+        let mut proof = proofer.generate(2).unwrap();
+        let (valid, trace) = proofer.verify_trace(&proof, "c", &root);
+        assert!(valid);
+        assert_eq!(trace.len(), tree.height());
+        assert_eq!(trace[0], hasher.hash(b"c"));
+        assert_eq!(
+            trace[1],
+            hasher.combine(&hasher.hash(b"c"), &hasher.hash(b"d"))
+        );
+        assert_eq!(trace.last().unwrap(), &root);
+
+        proof.path[1].hash = hasher.hash(b"tampered");
+        let (valid, bad_trace) = proofer.verify_trace(&proof, "c", &root);
+        assert!(!valid);
+        assert_eq!(bad_trace[..2], trace[..2]);
+        assert_ne!(bad_trace[2], trace[2]);
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_verify_standalone() {
+        let hasher = SHA256Hasher::new();
+        let tree = MerkleTree::new(hasher.clone(), ["a", "b", "c"]);
+        let proofer = DefaultProofer::new(hasher.clone(), tree.leaves());
+        let root = tree.root_hex();
+
+        let mut proof = proofer.generate(1).unwrap();
+        assert_eq!(proof.leaf_hash, Some(hasher.hash(b"b")));
+        assert!(proof.verify_standalone(&hasher, &root));
+
+        proof.leaf_hash = Some(hasher.hash(b"x"));
+        assert!(!proof.verify_standalone(&hasher, &root));
+
+        proof.leaf_hash = None;
+        assert!(!proof.verify_standalone(&hasher, &root));
+        assert!(proofer.verify(&proof, "b", &root));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_validate_levels() {
+        use crate::{error::ValidationError, node::Node};
+
+        let hasher = SHA256Hasher::new();
+        let tree = MerkleTree::new(hasher.clone(), ["a", "b", "c"]);
+        let proofer = DefaultProofer::new(hasher.clone(), tree.leaves());
+        assert_eq!(proofer.validate(), Ok(()));
+
+        let levels = proofer.levels.clone();
+        assert!(DefaultProofer::from_levels(hasher.clone(), levels.clone()).is_ok());
+        assert_eq!(
+            DefaultProofer::from_levels(hasher.clone(), vec![]).err(),
+            Some(ValidationError::Empty)
+        );
+
+        let mut truncated = levels.clone();
+        truncated[1].pop();
+        assert_eq!(
+            DefaultProofer::from_levels(hasher.clone(), truncated).err(),
+            Some(ValidationError::LevelSize {
+                level: 1,
+                expected: 2,
+                actual: 1
+            })
+        );
+
+        let mut tampered = levels.clone();
+        tampered[1][1] = Node::new_leaf(hasher.hash(b"x"));
+        assert_eq!(
+            DefaultProofer::from_levels(hasher.clone(), tampered).err(),
+            Some(ValidationError::HashMismatch { level: 1, index: 1 })
+        );
+
+        let mut rootless = levels;
+        rootless.pop();
+        assert_eq!(
+            DefaultProofer::from_levels(hasher, rootless).err(),
+            Some(ValidationError::MissingRoot)
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_path_bytes_roundtrip() {
+        let hasher = SHA256Hasher::new();
+        let data: Vec<Vec<u8>> = (0..600u16).map(|i| i.to_be_bytes().to_vec()).collect();
+        let tree = MerkleTree::new(hasher.clone(), &data);
+        let proofer = DefaultProofer::new(hasher, tree.leaves());
+
+        let proof = proofer.generate(411).unwrap();
+        let (directions, hashes) = proof.path_bytes();
+        assert_eq!(proof.path.len(), 10);
+        assert_eq!(directions.len(), 2);
+        assert_eq!(hashes.len(), 10 * 32);
+
+        let decoded = MerkleProof::from_path_bytes(&directions, &hashes, 32, 411).unwrap();
+        assert_eq!(decoded.path, proof.path);
+        assert!(proofer.verify(&decoded, &data[411], &tree.root_hex()));
+
+        assert!(MerkleProof::from_path_bytes(&directions, &hashes[1..], 32, 411).is_none());
+        assert!(MerkleProof::from_path_bytes(&directions[..1], &hashes, 32, 411).is_none());
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_flat_bytes_roundtrip() {
+        let hasher = SHA256Hasher::new();
+        let tree = MerkleTree::new(hasher.clone(), ["a", "b", "c", "d", "e"]);
+        let proofer = DefaultProofer::from_tree(hasher, &tree);
+
+        let proof = proofer.generate(4).unwrap();
+        let bytes = proof.to_flat_bytes();
+        assert_eq!(bytes.len(), proof.path.len() * 33);
+        assert_eq!(bytes[0], 1);
+
+        let decoded = MerkleProof::from_flat_bytes(&bytes, 4).unwrap();
+        assert_eq!(decoded.path, proof.path);
+        assert!(proofer.verify(&decoded, "e", &tree.root_hex()));
+
+        assert_eq!(
+            MerkleProof::from_flat_bytes(&bytes[..bytes.len() - 1], 4),
+            Err(ParseError::Truncated)
+        );
+        let mut invalid = bytes.clone();
+        invalid[33] = 2;
+        assert_eq!(
+            MerkleProof::from_flat_bytes(&invalid, 4),
+            Err(ParseError::InvalidDirection(2))
+        );
+        assert_eq!(
+            MerkleProof::from_flat_bytes(&[], 0).map(|proof| proof.path.len()),
+            Ok(0)
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_flat_bytes_with_shorter_digests() {
+        use crate::hasher::TruncatingHasher;
+
+        let hasher = TruncatingHasher::<_, 20>::new(SHA256Hasher::new());
+        let tree = MerkleTree::new(hasher.clone(), ["a", "b", "c", "d", "e"]);
+        let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+
+        let proof = proofer.generate(3).unwrap();
+        let bytes = proof.to_flat_bytes();
+        assert_eq!(bytes.len(), proof.path.len() * (1 + hasher.output_len()));
+
+        let decoded = MerkleProof::from_flat_bytes_with_len(&bytes, 3, hasher.output_len());
+        assert_eq!(decoded.as_ref().map(|proof| &proof.path), Ok(&proof.path));
+        assert!(proofer.verify(&decoded.unwrap(), "d", &tree.root_hex()));
+        assert_eq!(
+            MerkleProof::from_flat_bytes(&bytes, 3),
+            Err(ParseError::Truncated)
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_apply_update_keeps_proofs_fresh() {
+        let hasher = SHA256Hasher::new();
+        let data: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i]).collect();
+        let mut tree = MerkleTreeBuilder::new(hasher.clone())
+            .revocable(true)
+            .build(&data);
+        let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+        let mut proofs: Vec<MerkleProof> = (0..8).map(|i| proofer.generate(i).unwrap()).collect();
+
+        let deleted = 4;
+        tree.delete_leaf(&hasher, deleted).unwrap();
+        for level in 0..tree.height() - 1 {
+            let position = deleted >> level;
+            let new_hash = tree.level_hashes(level).unwrap()[position].clone();
+            for proof in &mut proofs {
+                proof.apply_update(level, position, &new_hash);
+            }
+        }
+
+        for (index, proof) in proofs.iter().enumerate() {
+            if index != deleted {
+                assert!(proofer.verify(proof, &data[index], &tree.root_hex()));
+            }
+        }
+        assert!(!proofs[0].apply_update(10, 0, "x"));
+        assert!(!proofs[0].apply_update(0, 2, "x"));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_prove_data() {
+        use crate::merkletree::MerkleTreeBuilder;
+
+        let hasher = SHA256Hasher::new();
+        let data = ["a", "b", "c", "b"];
+
+        let tree = MerkleTree::new(hasher.clone(), data);
+        let proofer = DefaultProofer::new(hasher.clone(), tree.leaves());
+        let proof = proofer.prove_data(b"b").unwrap();
+        assert_eq!(proof.leaf_index, 1);
+        assert!(proofer.verify(&proof, "b", &tree.root_hex()));
+        assert!(proofer.prove_data(b"z").is_none());
+
+        let tree = MerkleTreeBuilder::new(hasher.clone())
+            .bind_index(true)
+            .build(data);
+        let proofer = DefaultProofer::from_tree(hasher, &tree);
+        let proof = proofer.prove_data(b"c").unwrap();
+        assert_eq!(proof.leaf_index, 2);
+        assert!(proofer.verify(&proof, "c", &tree.root_hex()));
+    }
+
+    #[test]
+    fn test_implied_index() {
+        let data: Vec<[u8; 1]> = (0..11u8).map(|i| [i]).collect();
+        let tree = MerkleTree::new(DummyHasher, &data);
+        let proofer = DefaultProofer::new(DummyHasher, tree.leaves());
+
+        for (index, item) in data.iter().enumerate() {
+            let mut proof = proofer.generate(index).unwrap();
+            assert_eq!(proof.implied_index(), index);
+            assert!(proofer.verify_strict(&proof, item, &tree.root_hex()));
+
+            proof.leaf_index ^= 1;
+            assert!(!proofer.verify_strict(&proof, item, &tree.root_hex()));
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_verify_against_root_file() {
+        let algo = HashAlgorithm::Sha256;
+        let tree = MerkleTree::new(algo, ["a", "b", "c"]);
+        let proof = DefaultProofer::from_tree(algo, &tree).generate(1).unwrap();
+
+        let dir = std::env::temp_dir().join(format!("mt-root-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (data, root) = (dir.join("data"), dir.join("root"));
+        std::fs::write(&data, "b").unwrap();
+
+        let root_hex = tree.root_hex();
+        let formats = [
+            format!("{}\n", root_hex.to_uppercase()).into_bytes(),
+            hex::decode(&root_hex).unwrap(),
+        ];
+        for content in formats {
+            std::fs::write(&root, content).unwrap();
+            assert!(verify_against_root_file(&proof, &data, &root, algo).unwrap());
+        }
+
+        std::fs::write(&data, "a").unwrap();
+        assert!(!verify_against_root_file(&proof, &data, &root, algo).unwrap());
+
+        std::fs::write(&root, &root_hex[1..]).unwrap();
+        assert!(matches!(
+            verify_against_root_file(&proof, &data, &root, algo),
+            Err(MerkleError::InvalidRootFile { .. })
+        ));
+        std::fs::write(&root, &root_hex).unwrap();
+        assert!(matches!(
+            verify_against_root_file(&proof, dir.join("missing"), &root, algo),
+            Err(MerkleError::Io { .. })
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_verify_hex() {
+        use crate::error::ParseError;
+
+        let algo = HashAlgorithm::Sha256;
+        let tree = MerkleTree::new(algo, ["a", "b", "c"]);
+        let proofer = DefaultProofer::new(algo, tree.leaves());
+        let proof = proofer.generate(2).unwrap();
+        let proof_hex = proof.to_hex();
+        let root = tree.root_hex();
+
+        let decoded = MerkleProof::from_hex(&proof_hex).unwrap();
+        assert_eq!(decoded.path, proof.path);
+        assert_eq!(decoded.leaf_index, 2);
+
+        assert_eq!(verify_hex(&proof_hex, b"c", &root, algo), Ok(true));
+        assert_eq!(
+            verify_hex(&proof_hex, b"c", &root.to_uppercase(), algo),
+            Ok(true)
+        );
+        assert_eq!(verify_hex(&proof_hex, b"a", &root, algo), Ok(false));
+        assert_eq!(
+            verify_hex("zz", b"c", &root, algo),
+            Err(ParseError::InvalidHex)
+        );
+        assert_eq!(
+            verify_hex(&proof_hex, b"c", "xyz", algo),
+            Err(ParseError::InvalidHex)
+        );
+        assert_eq!(
+            verify_hex(&proof_hex[..proof_hex.len() - 2], b"c", &root, algo),
+            Err(ParseError::Truncated)
+        );
+        assert_eq!(
+            verify_hex(&(proof_hex.clone() + "00"), b"c", &root, algo),
+            Err(ParseError::TrailingBytes)
+        );
+
+        let mut bytes = proof.to_bytes();
+        bytes[12] = 7;
+        assert_eq!(
+            MerkleProof::from_bytes(&bytes),
+            Err(ParseError::InvalidDirection(7))
+        );
+    }
+
+    #[test]
+    fn test_full_path_matches_trace() {
+        let data = ["a", "b", "c", "d", "e"];
+        let tree = MerkleTree::new(DummyHasher, data);
+        let proofer = DefaultProofer::new(DummyHasher, tree.leaves());
+
+        for (index, item) in data.iter().enumerate() {
+            let full = proofer.full_path(index).unwrap();
+            let proof = proofer.generate(index).unwrap();
+            let (valid, trace) = proofer.verify_trace(&proof, item, &tree.root_hex());
+
+            assert!(valid);
+            assert_eq!(full.path, proof.path);
+            assert_eq!(full.leaf_hash, trace[0]);
+            assert_eq!(full.intermediate_hashes, trace[1..trace.len() - 1]);
+            assert_eq!(full.root_hash, tree.root_hex());
+        }
+        assert!(proofer.full_path(5).is_none());
+    }
+
+    /// Bitcoin's double SHA256 over raw digests.
+    #[cfg(feature = "sha256")]
+    struct DoubleSha256;
+
+    #[cfg(feature = "sha256")]
+    impl Hasher for DoubleSha256 {
+        fn hash(&self, input: &[u8]) -> String {
+            SHA256Hasher.hash(&hex::decode(SHA256Hasher.hash(input)).unwrap())
+        }
+
+        fn combine(&self, left: &str, right: &str) -> String {
+            self.hash(&hex::decode(left.to_string() + right).unwrap())
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_bitcoin_branch_of_block_100000() {
+        use crate::hasher::reverse_hex;
+
+        // Transaction 2 of block 100000, hashes in internal byte order.
+        let txid = reverse_hex("6359f0868171b1d194cbee1af2f16ea598ae8fad666d9b012c8ed2b79a236ec4")
+            .unwrap();
+        let root = reverse_hex("f3e94742aca4b5ef85488dc37c06c3282295ffec960994b2c0d5ac2a25a95766")
+            .unwrap();
+        let branch: Vec<[u8; 32]> = [
+            "1d0cb83721529a062d9675b98d6e5c587e4a770fc84ed00abc5a5de04568a6e9",
+            "15b88c5107195bf09eb9da89b83d95b3d070079a3c5c5d3d17d0dcd873fbdacc",
+        ]
+        .iter()
+        .map(|hash| hex::decode(hash).unwrap().try_into().unwrap())
+        .collect();
+
+        let mut proof = MerkleProof::from_bitcoin_branch(&branch, 2);
+        assert_eq!(proof.path[0].child_type, NodeChildType::Right);
+        assert_eq!(proof.path[1].child_type, NodeChildType::Left);
+        assert_eq!(proof.to_bitcoin_branch(), (branch, 2));
+
+        proof.leaf_hash = Some(txid);
+        assert!(proof.verify_standalone(&DoubleSha256, &root));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_new_from_raw_parts() {
+        let hasher = SHA256Hasher::new();
+        let data = ["a", "b", "c", "d", "e"];
+        let tree = MerkleTree::new(hasher.clone(), data);
+        let proofer = DefaultProofer::from_tree(hasher, &tree);
+
+        let generated = proofer.generate(2).unwrap();
+        let parts: Vec<([u8; 32], bool)> = generated
+            .path
+            .iter()
+            .map(|node| {
+                let hash = hex::decode(&node.hash).unwrap().try_into().unwrap();
+                (hash, node.child_type == NodeChildType::Left)
+            })
+            .collect();
+
+        let proof = MerkleProof::new(2, parts);
+        assert_eq!(proof.path, generated.path);
+        assert!(proof.leaf_hash.is_none());
+        assert!(proofer.verify(&proof, "c", &tree.root_hex()));
+        assert!(!proofer.verify(&proof, "d", &tree.root_hex()));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_is_well_formed() {
+        let tree = MerkleTree::new(SHA256Hasher, ["a", "b", "c"]);
+        let proofer = DefaultProofer::new(SHA256Hasher, tree.leaves());
+        let proof = proofer.generate(2).unwrap();
+
+        assert!(proof.is_well_formed(None));
+        assert!(proof.is_well_formed(Some(3)));
+        assert!(!proof.is_well_formed(Some(4)));
+
+        let mut bad_index = proof.clone();
+        bad_index.leaf_index = 3;
+        assert!(!bad_index.is_well_formed(None));
+
+        let mut empty_hash = proof.clone();
+        empty_hash.path[1].hash.clear();
+        assert!(!empty_hash.is_well_formed(None));
+
+        let mut uneven = proof.clone();
+        uneven.path[0].hash.push('0');
+        assert!(!uneven.is_well_formed(None));
+
+        let mut too_long = proof;
+        too_long.path = vec![too_long.path[0].clone(); usize::BITS as usize + 1];
+        assert!(!too_long.is_well_formed(None));
+    }
+
+    #[test]
+    fn test_from_leaf_hashes_generates_identical_proofs() {
+        let data: Vec<[u8; 1]> = (0..7u8).map(|i| [i]).collect();
+        let tree = MerkleTree::new(DummyHasher, &data);
+        let proofer = DefaultProofer::new(DummyHasher, tree.leaves());
+
+        let hashes: Vec<String> = tree
+            .leaves()
+            .iter()
+            .map(|leaf| leaf.hash().to_string())
+            .collect();
+        let restored = DefaultProofer::from_leaf_hashes(DummyHasher, hashes.clone());
+        let restored_tree = MerkleTree::from_leaf_hashes(DummyHasher, hashes);
+
+        assert_eq!(restored_tree.root_hex(), tree.root_hex());
+        for (index, item) in data.iter().enumerate() {
+            let proof = restored.generate(index).unwrap();
+            assert_eq!(proof, proofer.generate(index).unwrap());
+            assert!(restored.verify(&proof, item, &tree.root_hex()));
+        }
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_diff_pinpoints_wrong_levels() {
+        let hasher = SHA256Hasher::new();
+        let tree = MerkleTree::new(hasher.clone(), ["a", "b", "c", "d", "e"]);
+        let proofer = DefaultProofer::from_tree(hasher, &tree);
+        let proof = proofer.generate(2).unwrap();
+        assert!(proof.diff(&proof).is_empty());
+
+        let mut stale = proof.clone();
+        stale.path[1].hash = proofer.generate(0).unwrap().path[0].hash.clone();
+        stale.path[2].child_type = NodeChildType::Left;
+        assert_eq!(proof.diff(&stale), [1, 2]);
+        assert_eq!(stale.diff(&proof), [1, 2]);
+
+        let mut short = proof.clone();
+        short.path.truncate(1);
+        assert_eq!(proof.diff(&short), [1, 2]);
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_index_proof() {
+        let hasher = SHA256Hasher::new();
+        let tree = MerkleTree::new(hasher.clone(), ["a", "b", "c", "d", "e"]);
+        let proofer = DefaultProofer::from_tree(hasher, &tree);
+        let synced: Vec<&str> = tree.leaves_ref().iter().map(Node::hash).collect();
+
+        for (index, leaf_hash) in synced.iter().enumerate() {
+            let proof = proofer.generate_index_proof(index).unwrap();
+            assert_eq!(proof.path, proofer.generate(index).unwrap().path);
+            assert!(proofer.verify_index_proof(&proof, leaf_hash, &tree.root_hex()));
+        }
+        assert!(proofer.generate_index_proof(5).is_none());
+
+        // The proof of leaf 1 does not verify leaf 0, even though the leaves are siblings.
+        let mut proof = proofer.generate_index_proof(1).unwrap();
+        assert!(!proofer.verify_index_proof(&proof, synced[0], &tree.root_hex()));
+        proof.leaf_index = 0;
+        assert!(!proofer.verify_index_proof(&proof, synced[1], &tree.root_hex()));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_commutative_proofer() {
+        let hasher = CommutativeHasher::new(SHA256Hasher::new());
+        let root = |data: [&str; 4]| MerkleTree::new(hasher.clone(), data).root_hex();
+        let expected = root(["a", "b", "c", "d"]);
+        assert_eq!(root(["b", "a", "d", "c"]), expected);
+        assert_eq!(root(["c", "d", "a", "b"]), expected);
+        assert_eq!(root(["d", "c", "b", "a"]), expected);
+        assert_ne!(root(["a", "c", "b", "d"]), expected);
+
+        let tree = MerkleTree::new(hasher.clone(), ["a", "b", "c", "d"]);
+        let proofer: CommutativeProofer<_> = DefaultProofer::from_tree(hasher, &tree);
+        let mut proof = proofer.generate(1).unwrap();
+        assert!(proofer.verify(&proof, "b", &expected));
+        for node in &mut proof.path {
+            node.child_type = match node.child_type {
+                NodeChildType::Left => NodeChildType::Right,
+                NodeChildType::Right => NodeChildType::Left,
+            };
+        }
+        assert!(proofer.verify(&proof, "b", &expected));
+        assert!(!proofer.verify(&proof, "a", &expected));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_proof_context() {
+        let hasher = SHA256Hasher::new();
+        let tree = MerkleTree::new(hasher.clone(), ["a", "b", "c", "d", "e"]);
+        let proofer = DefaultProofer::from_tree(hasher, &tree);
+
+        let context = proofer.proof_context();
+        assert_eq!(
+            context,
+            ProofContext {
+                height: tree.height(),
+                leaf_count: 5,
+                hasher_name: "sha256".to_string(),
+            }
+        );
+
+        let proof = proofer.generate(4).unwrap();
+        assert!(context.accepts(&proof));
+
+        let mut short = proof.clone();
+        short.path.pop();
+        assert!(!context.accepts(&short));
+
+        let mut out_of_bounds = proofer.generate(1).unwrap();
+        out_of_bounds.leaf_index = 5;
+        assert!(!context.accepts(&out_of_bounds));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_verify_batch() {
+        let hasher = SHA256Hasher::new();
+        let data: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+        let tree = MerkleTree::new(hasher.clone(), &data);
+        let proofer = DefaultProofer::from_tree(hasher, &tree);
+        let root = tree.root_hex();
+
+        let mut items: Vec<(MerkleProof, String)> = data
+            .iter()
+            .enumerate()
+            .map(|(index, item)| (proofer.generate(index).unwrap(), item.clone()))
+            .collect();
+        items[7].1 = "tampered".to_string();
+
+        for chunk_size in [0, 1, 3, DEFAULT_VERIFY_CHUNK_SIZE, 5000] {
+            let results = proofer.verify_batch_chunked(&items, &root, chunk_size);
+            assert_eq!(results.len(), items.len());
+            assert!(!results[7]);
+            assert_eq!(results.iter().filter(|valid| **valid).count(), 999);
+        }
+        assert_eq!(
+            proofer.verify_batch(&items, &root),
+            proofer.verify_batch_chunked(&items, &root, 1)
+        );
+    }
+
+    #[test]
+    fn test_is_plausible_for_size() {
+        let hasher = DummyHasher;
+        for leaf_count in 1..=17 {
+            let data: Vec<String> = (0..leaf_count).map(|i| i.to_string()).collect();
+            let tree = MerkleTree::new(hasher.clone(), &data);
+            let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+
+            for index in 0..leaf_count {
+                let proof = proofer.generate(index).unwrap();
+                assert!(proof.is_plausible_for_size(leaf_count));
+                assert!(!proof.is_plausible_for_size(index));
+                assert!(!proof.is_plausible_for_size(2 * leaf_count));
+            }
+        }
+
+        let hasher = DummyHasher;
+        let tree = MerkleTree::new(hasher.clone(), ["a", "b", "c", "d", "e"]);
+        let mut proof = DefaultProofer::from_tree(hasher, &tree)
+            .generate(0)
+            .unwrap();
+        assert!(proof.is_plausible_for_size(8));
+        proof.path.pop();
+        assert!(!proof.is_plausible_for_size(5));
+        assert!(proof.is_plausible_for_size(4));
+
+        // The size comes from untrusted input and may not have a power of two above it.
+        proof.path = vec![proof.path[0].clone(); usize::BITS as usize];
+        assert!(!proof.is_plausible_for_size(usize::MAX));
+        assert!(!proof.is_plausible_for_size((1 << (usize::BITS - 1)) + 1));
+    }
+
+    #[test]
+    fn test_json_string_escapes() {
+        assert_eq!(json_string("ab12"), r#""ab12""#);
+        assert_eq!(json_string("a\"b\\c\n"), r#""a\"b\\c\u000a""#);
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_verify_report_json() {
+        let hasher = SHA256Hasher::new();
+        let tree = MerkleTree::new(hasher.clone(), ["a", "b", "c"]);
+        let proofer = DefaultProofer::from_tree(hasher.clone(), &tree);
+        let root = tree.root_hex();
+        let proof = proofer.generate(2).unwrap();
+
+        let leaf = hasher.hash(b"c");
+        let parent = hasher.combine(&leaf, &leaf);
+        let expected = format!(
+            r#"{{"leaf_index":2,"leaf_hash":"{leaf}","steps":[{{"sibling":"{leaf}","direction":"right","hash":"{parent}"}},{{"sibling":"{}","direction":"left","hash":"{root}"}}],"computed_root":"{root}","expected_root":"{root}","valid":true}}"#,
+            proof.path[1].hash
+        );
+        assert_eq!(proofer.verify_report_json(&proof, "c", &root), expected);
+
+        let report = proofer.verify_report_json(&proof, "b", &root);
+        assert!(report.ends_with(&format!(r#""expected_root":"{root}","valid":false}}"#)));
+    }
+
+    #[cfg(all(feature = "generic-array", feature = "sha256"))]
+    #[test]
+    fn test_proof_nodes_from_generic_arrays() {
+        use digest::Digest as _;
+
+        let hasher = SHA256Hasher::new();
+        let tree = MerkleTree::new(hasher.clone(), ["a", "b"]);
+        let proofer = DefaultProofer::from_tree(hasher, &tree);
+
+        let sibling = sha2::Sha256::digest(b"b");
+        let proof = MerkleProof {
+            path: vec![ProofNode::from((sibling, NodeChildType::Right))],
+            leaf_index: 0,
+            leaf_hash: None,
+        };
+        assert_eq!(proof.path, proofer.generate(0).unwrap().path);
+        assert!(proofer.verify(&proof, "a", &tree.root_hex()));
+
+        let raw: [u8; 32] = sibling.into();
+        assert_eq!(ProofNode::from((raw, NodeChildType::Right)), proof.path[0]);
+    }
 }
@@ -0,0 +1,129 @@
+//! Protocol Buffers messages for proofs, matching the schema shipped in `proto/mt.proto`, so
+//! proofs can be embedded in gRPC responses and decoded by code generated in any language.
+//!
+//! Hashes are carried as raw bytes rather than hex strings.
+
+use crate::{
+    error::ParseError,
+    node::NodeChildType,
+    proof::{MerkleProof, ProofNode},
+};
+
+/// The `mt.ProofStep` message: one sibling of a proof path.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ProofStepProto {
+    /// The raw bytes of the sibling hash.
+    #[prost(bytes = "vec", tag = "1")]
+    pub hash: Vec<u8>,
+    /// Whether the sibling is the left child.
+    #[prost(bool, tag = "2")]
+    pub is_left: bool,
+}
+
+/// The `mt.MerkleProof` message.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ProofProto {
+    /// The index of the proven leaf.
+    #[prost(uint64, tag = "1")]
+    pub leaf_index: u64,
+    /// The siblings from the leaf level up.
+    #[prost(message, repeated, tag = "2")]
+    pub path: Vec<ProofStepProto>,
+    /// The raw bytes of the leaf hash, if the proof carries it.
+    #[prost(bytes = "vec", optional, tag = "3")]
+    pub leaf_hash: Option<Vec<u8>>,
+}
+
+impl From<MerkleProof> for ProofProto {
+    /// # Panics
+    ///
+    /// Panics if a hash of the proof is not hex-encoded, which never happens for proofs
+    /// generated by this library.
+    fn from(proof: MerkleProof) -> Self {
+        let decode = |hash: &str| hex::decode(hash).expect("hash is not hex-encoded");
+
+        Self {
+            leaf_index: proof.leaf_index as u64,
+            path: proof
+                .path
+                .iter()
+                .map(|node| ProofStepProto {
+                    hash: decode(&node.hash),
+                    is_left: node.child_type == NodeChildType::Left,
+                })
+                .collect(),
+            leaf_hash: proof.leaf_hash.as_deref().map(decode),
+        }
+    }
+}
+
+impl TryFrom<ProofProto> for MerkleProof {
+    type Error = ParseError;
+
+    fn try_from(proto: ProofProto) -> Result<Self, Self::Error> {
+        let leaf_index = usize::try_from(proto.leaf_index)
+            .map_err(|_| ParseError::IndexOverflow(proto.leaf_index))?;
+
+        Ok(Self {
+            path: proto
+                .path
+                .iter()
+                .map(|step| ProofNode {
+                    hash: hex::encode(&step.hash),
+                    child_type: if step.is_left {
+                        NodeChildType::Left
+                    } else {
+                        NodeChildType::Right
+                    },
+                })
+                .collect(),
+            leaf_index,
+            leaf_hash: proto.leaf_hash.map(hex::encode),
+        })
+    }
+}
+
+#[cfg(all(test, feature = "sha256"))]
+mod tests {
+    use super::*;
+    use crate::{
+        hasher::SHA256Hasher,
+        merkletree::MerkleTree,
+        proof::{DefaultProofer, Proofer},
+    };
+    use prost::Message;
+
+    #[test]
+    fn test_proof_round_trips_through_protobuf() {
+        let hasher = SHA256Hasher::new();
+        let tree = MerkleTree::new(hasher.clone(), ["a", "b", "c", "d", "e"]);
+        let proofer = DefaultProofer::from_tree(hasher, &tree);
+
+        for index in 0..tree.len() {
+            let proof = proofer.generate(index).unwrap();
+            let proto = ProofProto::from(proof.clone());
+            assert_eq!(proto.path.len(), proof.path.len());
+            assert!(proto.path.iter().all(|step| step.hash.len() == 32));
+
+            let decoded = ProofProto::decode(proto.encode_to_vec().as_slice()).unwrap();
+            assert_eq!(MerkleProof::try_from(decoded), Ok(proof));
+        }
+    }
+
+    #[test]
+    fn test_proof_without_leaf_hash() {
+        let proto = ProofProto {
+            leaf_index: 1,
+            path: vec![ProofStepProto {
+                hash: vec![0xab; 4],
+                is_left: true,
+            }],
+            leaf_hash: None,
+        };
+
+        let proof = MerkleProof::try_from(proto).unwrap();
+        assert_eq!(proof.path[0].hash, "abababab");
+        assert_eq!(proof.path[0].child_type, NodeChildType::Left);
+        assert!(proof.leaf_hash.is_none());
+    }
+}
@@ -0,0 +1,52 @@
+//! Helpers for sparse Merkle trees, where most leaves are empty and whole subtrees can be
+//! replaced by a precomputed constant.
+
+use crate::hasher::Hasher;
+
+/// Precomputes the roots of empty subtrees of every height up to `levels`.
+///
+/// The hash at index `i` is the root of a complete subtree of `2^i` empty leaves: index 0 is
+/// `hasher.empty_hash()`, and each following one combines the previous one with itself.
+///
+/// # Arguments
+///
+/// * `hasher` - The hasher of the sparse tree.
+/// * `levels` - The height of the highest empty subtree needed.
+///
+/// # Returns
+///
+/// The `levels + 1` empty-subtree hashes, from the leaves up.
+pub fn empty_subtree_hashes<H>(hasher: &H, levels: usize) -> Vec<String>
+where
+    H: Hasher,
+{
+    let mut hashes = Vec::with_capacity(levels + 1);
+    hashes.push(hasher.empty_hash());
+    for level in 0..levels {
+        let below = &hashes[level];
+        hashes.push(hasher.combine(below, below));
+    }
+    hashes
+}
+
+#[cfg(all(test, feature = "sha256"))]
+mod tests {
+    use super::*;
+    use crate::{hasher::SHA256Hasher, merkletree::MerkleTree};
+
+    #[test]
+    fn test_empty_subtree_hashes_match_trees_of_empty_leaves() {
+        let hasher = SHA256Hasher::new();
+        let hashes = empty_subtree_hashes(&hasher, 4);
+        assert_eq!(hashes.len(), 5);
+        assert_eq!(
+            hashes[0],
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+
+        for (level, hash) in hashes.iter().enumerate().skip(1) {
+            let tree = MerkleTree::new(hasher.clone(), vec![b""; 1 << level]);
+            assert_eq!(&tree.root_hex(), hash);
+        }
+    }
+}
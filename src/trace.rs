@@ -0,0 +1,182 @@
+//! Records every combine of a tree build, so an auditor can replay them and confirm that the
+//! build is deterministic and leads to the published root. Only available with the `trace`
+//! feature, since it keeps three hashes per internal node.
+
+use crate::{hasher::Hasher, merkletree::TreeOptions};
+
+/// A single combine of a build: two children hashed into their parent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CombineStep {
+    /// The level of the parent, 1 being the parents of the leaves.
+    pub level: usize,
+    /// The hash of the left child.
+    pub left: String,
+    /// The hash of the right child, equal to the left one when it is a padding duplicate.
+    pub right: String,
+    /// The hash of the parent.
+    pub parent: String,
+}
+
+/// The combines of a build, in the order they were made, as returned by
+/// `MerkleTree::build_trace`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildTrace {
+    steps: Vec<CombineStep>,
+}
+
+impl BuildTrace {
+    /// Returns the recorded combines, level by level from the leaves up; the last one produces
+    /// the top node of the tree.
+    pub fn steps(&self) -> &[CombineStep] {
+        &self.steps
+    }
+
+    /// Returns the hash of the top node, produced by the last combine, or `None` for a tree of
+    /// a single leaf. It is the root hash of the tree unless the leaf count is committed or
+    /// context is bound to the root.
+    pub fn top(&self) -> Option<&str> {
+        self.steps.last().map(|step| step.parent.as_str())
+    }
+
+    /// Replays the build from `leaves`, level by level, with `hasher`.
+    ///
+    /// Each level is paired as the build pairs it, padding odd levels as `options` say, and
+    /// every pair must match the next recorded combine, so the trace is checked to start from
+    /// the leaves and to feed each parent into the level above, not just to hold valid
+    /// combines.
+    ///
+    /// # Arguments
+    ///
+    /// * `hasher` - The hasher the tree was built with.
+    /// * `leaves` - The leaf hashes of the tree, in order.
+    /// * `options` - The options the tree was built with.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the recorded combines are exactly those of a build over `leaves`.
+    pub fn replay<H>(&self, hasher: &H, leaves: &[String], options: &TreeOptions) -> bool
+    where
+        H: Hasher,
+    {
+        let padding_hash = options.padding_hash(hasher);
+        let mut steps = self.steps.iter();
+        let mut current = leaves.to_vec();
+        let mut level = 1;
+
+        while current.len() > 1 {
+            let mut next = Vec::with_capacity(current.len().div_ceil(2));
+            for pair in current.chunks(2) {
+                let left = &pair[0];
+                let right = match pair.get(1) {
+                    Some(right) => right,
+                    None => padding_hash.as_ref().unwrap_or(left),
+                };
+
+                match steps.next() {
+                    Some(step)
+                        if step.level == level
+                            && &step.left == left
+                            && &step.right == right
+                            && hasher.combine(left, right) == step.parent =>
+                    {
+                        next.push(step.parent.clone())
+                    }
+                    _ => return false,
+                }
+            }
+            current = next;
+            level += 1;
+        }
+
+        steps.next().is_none()
+    }
+
+    /// Records a combine.
+    pub(crate) fn record(&mut self, level: usize, left: &str, right: &str, parent: &str) {
+        self.steps.push(CombineStep {
+            level,
+            left: left.to_string(),
+            right: right.to_string(),
+            parent: parent.to_string(),
+        });
+    }
+
+    /// Appends the combines of a subtree whose leaves sit at `level_offset` in this tree.
+    ///
+    /// Subtrees are appended one after the other, so call `sort_by_level` once they all are.
+    pub(crate) fn append(&mut self, other: BuildTrace, level_offset: usize) {
+        self.steps
+            .extend(other.steps.into_iter().map(|step| CombineStep {
+                level: step.level + level_offset,
+                ..step
+            }));
+    }
+
+    /// Orders the combines level by level, keeping their order within each level, e.g. after
+    /// appending subtrees from left to right.
+    pub(crate) fn sort_by_level(&mut self) {
+        self.steps.sort_by_key(|step| step.level);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hasher::DummyHasher, merkletree::PaddingStrategy};
+
+    #[test]
+    fn test_replay_detects_forged_step() {
+        let hasher = DummyHasher;
+        let options = TreeOptions::default();
+        let leaves = vec![hasher.hash(b"a"), hasher.hash(b"b")];
+        let parent = hasher.combine(&leaves[0], &leaves[1]);
+
+        let mut trace = BuildTrace::default();
+        trace.record(1, &leaves[0], &leaves[1], &parent);
+        assert!(trace.replay(&hasher, &leaves, &options));
+
+        let mut forged = BuildTrace::default();
+        forged.record(1, &leaves[0], &leaves[1], &hasher.hash(b"forged"));
+        assert!(!forged.replay(&hasher, &leaves, &options));
+
+        // Trailing combines past the top are rejected.
+        let mut longer = trace.clone();
+        longer.append(trace.clone(), 1);
+        assert_eq!(longer.steps()[1].level, 2);
+        assert!(!longer.replay(&hasher, &leaves, &options));
+    }
+
+    #[test]
+    fn test_replay_checks_linkage_to_the_leaves() {
+        let hasher = DummyHasher;
+        let options = TreeOptions::default();
+        let leaves: Vec<String> = ["a", "b", "c"]
+            .iter()
+            .map(|d| hasher.hash(d.as_bytes()))
+            .collect();
+
+        // Valid combines of unrelated hashes that end in the right top are not enough.
+        let (x, y) = (hasher.hash(b"x"), hasher.hash(b"y"));
+        let (left, right) = (
+            hasher.combine(&x, &y),
+            hasher.combine(&leaves[2], &leaves[2]),
+        );
+        let top = hasher.combine(&left, &right);
+        let mut unrelated = BuildTrace::default();
+        unrelated.record(1, &x, &y, &left);
+        unrelated.record(1, &leaves[2], &leaves[2], &right);
+        unrelated.record(2, &left, &right, &top);
+        assert!(!unrelated.replay(&hasher, &leaves, &options));
+
+        let mut honest = BuildTrace::default();
+        let left = hasher.combine(&leaves[0], &leaves[1]);
+        honest.record(1, &leaves[0], &leaves[1], &left);
+        honest.record(1, &leaves[2], &leaves[2], &right);
+        honest.record(2, &left, &right, &hasher.combine(&left, &right));
+        assert!(honest.replay(&hasher, &leaves, &options));
+
+        // The padding must be the one of the options.
+        let hash_empty = options.padding(PaddingStrategy::HashEmpty);
+        assert!(!honest.replay(&hasher, &leaves, &hash_empty));
+    }
+}